@@ -0,0 +1,270 @@
+use anyhow::{Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::h5::H5Path;
+
+/// Named groups that can be used with `cd :name`.
+///
+/// Bookmarks belong to a single file or are global and apply to all files.
+/// All bookmarks are stored in a single TOML file with the files keyed by their path.
+pub struct Bookmarks {
+    stored: StoredBookmarks,
+    /// The file that file-specific bookmarks belong to.
+    file: String,
+    path: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Scope {
+    File,
+    Global,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct StoredBookmarks {
+    global: BTreeMap<String, String>,
+    files: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Bookmarks {
+    pub fn new(path: PathBuf, file: String) -> Self {
+        Self {
+            stored: StoredBookmarks::default(),
+            file,
+            path,
+        }
+    }
+
+    /// Load the bookmarks from the file at `path`.
+    pub fn load(path: PathBuf, file: String) -> Self {
+        let mut bookmarks = Self::new(path, file);
+        match read(&bookmarks.path) {
+            Ok(Some(stored)) => bookmarks.stored = stored,
+            Ok(None) => info!("No bookmarks file at {}", bookmarks.path.display()),
+            Err(err) => info!("No bookmarks loaded: {err:#}"),
+        }
+        bookmarks
+    }
+
+    /// Look up a bookmark, bookmarks of the current file take precedence over global ones.
+    pub fn get(&self, name: &str) -> Option<(H5Path, Scope)> {
+        self.of_scope(Scope::File)
+            .get(name)
+            .map(|path| (H5Path::from(path.as_str()), Scope::File))
+            .or_else(|| {
+                self.stored
+                    .global
+                    .get(name)
+                    .map(|path| (H5Path::from(path.as_str()), Scope::Global))
+            })
+    }
+
+    pub fn contains(&self, name: &str, scope: Scope) -> bool {
+        self.of_scope(scope).contains_key(name)
+    }
+
+    /// Return all bookmarks sorted by name, global bookmarks are shadowed by file bookmarks.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, H5Path, Scope)> {
+        let file = self.of_scope(Scope::File);
+        let global = self
+            .stored
+            .global
+            .iter()
+            .filter(|(name, _)| !file.contains_key(*name))
+            .map(|(name, path)| (name, path, Scope::Global));
+        let mut all: Vec<_> = file
+            .iter()
+            .map(|(name, path)| (name, path, Scope::File))
+            .chain(global)
+            .collect();
+        all.sort_by_key(|(name, _, _)| *name);
+        all.into_iter()
+            .map(|(name, path, scope)| (name.as_str(), H5Path::from(path.as_str()), scope))
+    }
+
+    /// Add a bookmark and write it to the bookmark file.
+    pub fn add(&mut self, name: String, path: &H5Path, scope: Scope) -> Result<()> {
+        let file = self.file.clone();
+        self.update(|stored| {
+            let bookmarks = match scope {
+                Scope::File => stored.files.entry(file).or_default(),
+                Scope::Global => &mut stored.global,
+            };
+            bookmarks.insert(name, path.as_raw().to_string());
+        })
+    }
+
+    /// Remove a bookmark and write the change to the bookmark file.
+    ///
+    /// Returns `false` if there is no such bookmark.
+    pub fn remove(&mut self, name: &str, scope: Scope) -> Result<bool> {
+        let file = self.file.clone();
+        let mut removed = false;
+        self.update(|stored| {
+            removed = match scope {
+                Scope::File => {
+                    let removed = stored
+                        .files
+                        .get_mut(&file)
+                        .is_some_and(|bookmarks| bookmarks.remove(name).is_some());
+                    stored.files.retain(|_, bookmarks| !bookmarks.is_empty());
+                    removed
+                }
+                Scope::Global => stored.global.remove(name).is_some(),
+            };
+        })?;
+        Ok(removed)
+    }
+
+    fn of_scope(&self, scope: Scope) -> &BTreeMap<String, String> {
+        static EMPTY: BTreeMap<String, String> = BTreeMap::new();
+        match scope {
+            Scope::File => self.stored.files.get(&self.file).unwrap_or(&EMPTY),
+            Scope::Global => &self.stored.global,
+        }
+    }
+
+    /// Apply a change to the stored bookmarks and write them.
+    ///
+    /// Bookmarks that were written by other sessions in the meantime are preserved.
+    /// Nothing is written if the existing file cannot be read.
+    fn update(&mut self, change: impl FnOnce(&mut StoredBookmarks)) -> Result<()> {
+        if let Some(stored) = read(&self.path)? {
+            self.stored = stored;
+        }
+        change(&mut self.stored);
+        if let Some(parent) = self.path.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, toml::to_string(&self.stored)?)?;
+        Ok(())
+    }
+}
+
+/// Read the bookmark file, `None` if it does not exist.
+fn read(path: &Path) -> Result<Option<StoredBookmarks>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to read bookmarks file {}", path.display()));
+        }
+    };
+    toml::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse bookmarks file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("h5sh-bookmarks-{name}-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn file_bookmarks_shadow_global_ones() {
+        let path = temp_path("shadow");
+        let mut bookmarks = Bookmarks::new(path.clone(), "/data/a.h5".to_string());
+        bookmarks
+            .add(
+                "det".to_string(),
+                &H5Path::from("/entry/det"),
+                Scope::Global,
+            )
+            .unwrap();
+        bookmarks
+            .add(
+                "sample".to_string(),
+                &H5Path::from("/entry/sample"),
+                Scope::Global,
+            )
+            .unwrap();
+        bookmarks
+            .add(
+                "det".to_string(),
+                &H5Path::from("/entry/det_1"),
+                Scope::File,
+            )
+            .unwrap();
+
+        assert_eq!(
+            bookmarks.get("det"),
+            Some((H5Path::from("/entry/det_1"), Scope::File))
+        );
+        assert_eq!(
+            bookmarks.iter().collect::<Vec<_>>(),
+            vec![
+                ("det", H5Path::from("/entry/det_1"), Scope::File),
+                ("sample", H5Path::from("/entry/sample"), Scope::Global),
+            ]
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn bookmarks_are_stored_per_file() {
+        let path = temp_path("per-file");
+        let mut a = Bookmarks::new(path.clone(), "/data/a.h5".to_string());
+        a.add("det".to_string(), &H5Path::from("/entry/det"), Scope::File)
+            .unwrap();
+        a.add("root".to_string(), &H5Path::root(), Scope::Global)
+            .unwrap();
+
+        let b = Bookmarks::load(path.clone(), "/data/b.h5".to_string());
+        assert_eq!(b.get("det"), None);
+        assert_eq!(b.get("root"), Some((H5Path::root(), Scope::Global)));
+
+        let a = Bookmarks::load(path.clone(), "/data/a.h5".to_string());
+        assert_eq!(
+            a.get("det"),
+            Some((H5Path::from("/entry/det"), Scope::File))
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn remove_bookmark() {
+        let path = temp_path("remove");
+        let mut bookmarks = Bookmarks::new(path.clone(), "/data/a.h5".to_string());
+        bookmarks
+            .add("det".to_string(), &H5Path::from("/entry/det"), Scope::File)
+            .unwrap();
+        assert!(!bookmarks.remove("det", Scope::Global).unwrap());
+        assert!(bookmarks.remove("det", Scope::File).unwrap());
+        assert!(!bookmarks.remove("det", Scope::File).unwrap());
+        assert_eq!(bookmarks.get("det"), None);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap().trim(),
+            "[global]\n\n[files]"
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn invalid_file_is_not_overwritten() {
+        let path = temp_path("invalid");
+        let content = "[global]\ndet = \"/entry/det\"\n[files\n";
+        std::fs::write(&path, content).unwrap();
+        let mut bookmarks = Bookmarks::load(path.clone(), "/data/a.h5".to_string());
+        assert!(
+            bookmarks
+                .add("root".to_string(), &H5Path::root(), Scope::Global)
+                .is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -3,6 +3,10 @@ use crossterm::{queue, style::Print};
 use std::path::PathBuf;
 use std::process::exit;
 
+use crate::config::EditMode;
+use crate::h5::{Driver, LibraryVersion, OpenOptions};
+use crate::units;
+
 /// Interactive shell for HDF5 files.
 #[derive(Parser, Debug)]
 #[command(version, about, long_about)]
@@ -36,6 +40,62 @@ struct CliOpenArgs {
     /// Control color output.
     #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
     pub color: ColorChoice,
+
+    /// Path to a configuration file.
+    ///
+    /// Defaults to `h5sh/config.toml` in the user's config directory.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Key bindings for editing commands.
+    ///
+    /// Overrides `edit_mode` in the configuration file.
+    #[arg(long, value_enum)]
+    pub edit_mode: Option<EditMode>,
+
+    /// Open the file for single-writer-multiple-reader access.
+    ///
+    /// Use this to read a file while another program writes it in SWMR mode.
+    #[arg(long)]
+    pub swmr: bool,
+
+    /// Low-level driver for reading the file.
+    #[arg(long, value_enum, default_value_t = DriverChoice::Sec2)]
+    pub driver: DriverChoice,
+
+    /// Size of each member of a family file, e.g., '1GiB'.
+    ///
+    /// Defaults to the size of the first member.
+    #[arg(long, value_parser = parse_byte_size)]
+    pub family_size: Option<usize>,
+
+    /// Extension of the metadata file of a split file.
+    ///
+    /// Defaults to '-m.h5'.
+    #[arg(long)]
+    pub meta_ext: Option<String>,
+
+    /// Extension of the raw data file of a split file.
+    ///
+    /// Defaults to '-r.h5'.
+    #[arg(long)]
+    pub raw_ext: Option<String>,
+
+    /// Size of the metadata cache, e.g., '32MiB'.
+    ///
+    /// HDF5 allows at most 128MiB.
+    #[arg(long, value_parser = parse_byte_size)]
+    pub metadata_cache: Option<usize>,
+
+    /// Size of the chunk cache of each dataset, e.g., '256MiB'.
+    #[arg(long, value_parser = parse_byte_size)]
+    pub chunk_cache: Option<usize>,
+
+    /// Bounds of library versions for object formats as LOW or LOW,HIGH.
+    ///
+    /// Versions are earliest, v1.8, v1.10, v1.12, v1.14, and latest, e.g., 'v1.10,latest'.
+    #[arg(long, value_name = "LOW[,HIGH]", value_parser = parse_libver_bounds)]
+    pub libver: Option<(LibraryVersion, LibraryVersion)>,
 }
 
 #[derive(Args, Debug)]
@@ -68,6 +128,9 @@ pub enum Commands {
 pub struct OpenArgs {
     pub path: PathBuf,
     pub color: bool,
+    pub config: Option<PathBuf>,
+    pub edit_mode: Option<EditMode>,
+    pub open_options: OpenOptions,
 }
 
 #[derive(Debug)]
@@ -75,6 +138,20 @@ pub struct SelfArgs {
     pub command: SelfCommand,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum DriverChoice {
+    /// The default driver.
+    Sec2,
+    /// Use buffered I/O from the C standard library.
+    Stdio,
+    /// Read the whole file into memory.
+    Core,
+    /// A file split into members, the path must contain an integer format like 'data-%05d.h5'.
+    Family,
+    /// Metadata and raw data in separate files, the path is the name without extension.
+    Split,
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum ColorChoice {
     Auto,
@@ -118,12 +195,46 @@ fn normalize_command(command: CliCommands) -> Commands {
 }
 
 fn normalize_open_args(open_args: CliOpenArgs) -> OpenArgs {
+    let open_options = normalize_open_options(&open_args);
     let Some(path) = open_args.path else {
         usage_error("Specify a path to open.");
     };
     OpenArgs {
         path,
         color: normalize_color_choice(open_args.color),
+        config: open_args.config,
+        edit_mode: open_args.edit_mode,
+        open_options,
+    }
+}
+
+fn normalize_open_options(open_args: &CliOpenArgs) -> OpenOptions {
+    if open_args.family_size.is_some() && open_args.driver != DriverChoice::Family {
+        usage_error("'--family-size' requires '--driver family'.");
+    }
+    if (open_args.meta_ext.is_some() || open_args.raw_ext.is_some())
+        && open_args.driver != DriverChoice::Split
+    {
+        usage_error("'--meta-ext' and '--raw-ext' require '--driver split'.");
+    }
+    let driver = match open_args.driver {
+        DriverChoice::Sec2 => Driver::Default,
+        DriverChoice::Stdio => Driver::Stdio,
+        DriverChoice::Core => Driver::Core,
+        DriverChoice::Family => Driver::Family {
+            member_size: open_args.family_size,
+        },
+        DriverChoice::Split => Driver::Split {
+            meta_ext: open_args.meta_ext.as_deref().unwrap_or("-m.h5").to_string(),
+            raw_ext: open_args.raw_ext.as_deref().unwrap_or("-r.h5").to_string(),
+        },
+    };
+    OpenOptions {
+        swmr: open_args.swmr,
+        driver,
+        metadata_cache_size: open_args.metadata_cache,
+        chunk_cache_size: open_args.chunk_cache,
+        libver_bounds: open_args.libver,
     }
 }
 
@@ -135,6 +246,27 @@ fn normalize_color_choice(arg: ColorChoice) -> bool {
     }
 }
 
+fn parse_byte_size(s: &str) -> Result<usize, String> {
+    let size = units::parse_size(s)?;
+    usize::try_from(size).map_err(|_| format!("Size is too large: '{s}'"))
+}
+
+fn parse_libver_bounds(s: &str) -> Result<(LibraryVersion, LibraryVersion), String> {
+    let parse = |version: &str| match version.trim() {
+        "earliest" => Ok(LibraryVersion::Earliest),
+        "v1.8" => Ok(LibraryVersion::V18),
+        "v1.10" => Ok(LibraryVersion::V110),
+        "v1.12" => Ok(LibraryVersion::V112),
+        "v1.14" => Ok(LibraryVersion::V114),
+        "latest" => Ok(LibraryVersion::LATEST),
+        _ => Err(format!("Unknown library version: '{version}'")),
+    };
+    match s.split_once(',') {
+        Some((low, high)) => Ok((parse(low)?, parse(high)?)),
+        None => Ok((parse(s)?, LibraryVersion::LATEST)),
+    }
+}
+
 fn usage_error(message: &str) -> ! {
     let _ = queue!(
         std::io::stdout(),
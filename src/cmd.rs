@@ -1,7 +1,9 @@
 use indexmap::IndexMap;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::rc::Rc;
 
+use crate::bookmarks::Scope;
 use crate::commands;
 use crate::h5::{H5Error, H5File, H5Path};
 use crate::shell::Shell;
@@ -15,20 +17,31 @@ pub trait Command {
 pub struct Commands {
     base_commands: IndexMap<String, Rc<dyn Command>>,
     aliases: IndexMap<String, String>,
+    default_flags: IndexMap<String, String>,
 }
 
 impl Commands {
     pub fn new() -> Commands {
         let mut cmds: IndexMap<String, Rc<dyn Command>> = IndexMap::new();
         cmds.insert("a".to_string(), Rc::new(commands::Attr));
+        cmds.insert("bookmark".to_string(), Rc::new(commands::Bookmark));
         cmds.insert("cd".to_string(), Rc::new(commands::Cd));
         cmds.insert("cat".to_string(), Rc::new(commands::Cat));
+        cmds.insert("dirs".to_string(), Rc::new(commands::Dirs));
         cmds.insert("exit".to_string(), Rc::new(commands::Exit));
         cmds.insert("find".to_string(), Rc::new(commands::Find));
+        cmds.insert("foreach".to_string(), Rc::new(commands::Foreach));
         cmds.insert("help".to_string(), Rc::new(commands::Help));
+        cmds.insert("history".to_string(), Rc::new(commands::History));
+        cmds.insert("info".to_string(), Rc::new(commands::Info));
         cmds.insert("inspect".to_string(), Rc::new(commands::Inspect));
         cmds.insert("ls".to_string(), Rc::new(commands::Ls));
+        cmds.insert("popd".to_string(), Rc::new(commands::Popd));
+        cmds.insert("pushd".to_string(), Rc::new(commands::Pushd));
         cmds.insert("pwd".to_string(), Rc::new(commands::Pwd));
+        cmds.insert("refresh".to_string(), Rc::new(commands::Refresh));
+        cmds.insert("set".to_string(), Rc::new(commands::Set));
+        cmds.insert("watch".to_string(), Rc::new(commands::Watch));
 
         let mut aliases = IndexMap::new();
         aliases.insert("fd".to_string(), "find".to_string());
@@ -41,6 +54,7 @@ impl Commands {
         Self {
             base_commands: cmds,
             aliases,
+            default_flags: IndexMap::new(),
         }
     }
 
@@ -60,6 +74,16 @@ impl Commands {
         self.aliases.get(name).map(|s| s.as_str())
     }
 
+    pub fn get_default_flags(&self, name: &str) -> Option<&str> {
+        self.default_flags.get(name).map(|s| s.as_str())
+    }
+
+    /// Set flags that get inserted before the arguments of a base command.
+    pub fn set_default_flags(&mut self, name: &str, flags: &str) {
+        self.default_flags
+            .insert(name.to_string(), flags.to_string());
+    }
+
     #[cfg(test)] // for now not accessible to users
     pub fn add_alias(&mut self, name: &str, alias: &str) {
         self.aliases.insert(name.to_string(), alias.to_string());
@@ -68,6 +92,42 @@ impl Commands {
     pub fn keys(&self) -> impl Iterator<Item = &String> {
         self.base_commands.keys().chain(self.aliases.keys())
     }
+
+    /// Return the argument parsers of all commands and aliases.
+    ///
+    /// The parsers are built, so they include generated arguments like `--help`.
+    pub fn arg_parsers(&self) -> HashMap<String, clap::Command> {
+        let mut parsers: HashMap<String, clap::Command> = self
+            .base_commands
+            .iter()
+            .map(|(name, cmd)| {
+                let mut parser = cmd.arg_parser();
+                parser.build();
+                (name.clone(), parser)
+            })
+            .collect();
+        for name in self.aliases.keys() {
+            if let Some(parser) = self
+                .resolve_alias_command(name)
+                .and_then(|base| parsers.get(base))
+            {
+                parsers.insert(name.clone(), parser.clone());
+            }
+        }
+        parsers
+    }
+
+    /// Return the name of the base command that an alias runs.
+    fn resolve_alias_command<'a>(&'a self, mut name: &'a str) -> Option<&'a str> {
+        // Bound the number of steps to protect against cyclic aliases.
+        for _ in 0..=self.aliases.len() {
+            match self.get_alias(name) {
+                Some(alias) => name = alias.split_whitespace().next()?,
+                None => return self.base_commands.contains_key(name).then_some(name),
+            }
+        }
+        None
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -76,6 +136,26 @@ pub enum CommandOutcome {
     KeepRunning,
     /// Change the working group.
     ChangeWorkingGroup(H5Path),
+    /// Change the working group and replace the group stack.
+    ChangeGroupStack {
+        working_group: H5Path,
+        stack: Vec<H5Path>,
+    },
+    /// Set a shell variable.
+    SetVariable { name: String, value: String },
+    /// Add or replace a bookmark.
+    AddBookmark {
+        name: String,
+        path: H5Path,
+        scope: Scope,
+    },
+    /// Remove a bookmark.
+    RemoveBookmark { name: String, scope: Scope },
+    /// Execute each input line in turn.
+    Foreach {
+        inputs: Vec<String>,
+        stop_on_error: bool,
+    },
     /// Exit the shell after a failure without processing further commands.
     ExitFailure,
     /// Exit the shell without processing further commands.
@@ -90,6 +170,8 @@ pub enum CommandError {
     NoMessage,
     /// The command failed and recovery is not reliably possible.
     Critical(String),
+    /// The user cancelled the command with Ctrl-C.
+    Interrupted,
 }
 
 pub type CmdResult = Result<CommandOutcome, CommandError>;
@@ -100,6 +182,7 @@ impl Display for CommandError {
             CommandError::Error(msg) => f.write_str(msg),
             CommandError::NoMessage => Ok(()),
             CommandError::Critical(msg) => f.write_str(msg),
+            CommandError::Interrupted => f.write_str("Interrupted"),
         }
     }
 }
@@ -1,10 +1,11 @@
 use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
 use crate::h5;
-use crate::h5::{H5File, H5Object, H5Path};
+use crate::h5::{H5File, H5Object, H5Path, ObjectPath};
+use crate::interrupt;
 use crate::output::Printer;
 use crate::shell::Shell;
 use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
-use std::io::{self, stdout};
+use std::io;
 
 #[derive(Clone, Copy, Default)]
 pub struct Attr;
@@ -14,10 +15,27 @@ impl Command for Attr {
         let Ok(args) = Arguments::from_arg_matches(&args) else {
             return Err(CommandError::Critical("Failed to extract args".to_string()));
         };
-        let parent_object = load_parent_object(&args.path, shell, file)?;
-        let attr_names = collect_attributes(&parent_object, args.attr)?;
-        let attrs = load_attributes(&parent_object, attr_names.as_ref())?;
-        show_attrs(attr_names, attrs, shell.printer())?;
+        let printer = shell.printer();
+        if args.paths.is_empty() {
+            return show_attrs_of(&ObjectPath::from(String::new()), shell, file);
+        }
+        if let Some(attr_names) = trailing_attr_names(&args.paths, shell, file) {
+            return show_attrs_of_location(
+                &args.paths[0].location_path,
+                Some(attr_names),
+                shell,
+                file,
+            );
+        }
+        for (index, path) in args.paths.iter().enumerate() {
+            if args.paths.len() > 1 {
+                if index > 0 {
+                    printer.println("");
+                }
+                printer.println(format!("{path}:"));
+            }
+            show_attrs_of(path, shell, file)?;
+        }
         Ok(CommandOutcome::KeepRunning)
     }
 
@@ -29,12 +47,65 @@ impl Command for Attr {
 /// Print attributes.
 #[derive(Parser, Debug)]
 #[command(name("a"))]
+#[command(after_help = "Examples:
+Print all attributes of the working group:
+  a
+
+Print a single attribute of several datasets:
+  a detector_?/data@units
+
+Print several attributes of one dataset:
+  a data units long_name")]
 struct Arguments {
-    /// Path of a dataset or group.
-    path: Option<H5Path>,
+    /// Paths of datasets or groups, optionally with an attribute name as PATH@ATTR.
+    /// Prints all attributes if no attribute name is given.
+    /// Names of attributes of the first path can also follow it as PATH ATTR...
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<ObjectPath>,
+}
+
+fn show_attrs_of(path: &ObjectPath, shell: &Shell, file: &H5File) -> CmdResult {
+    let attr_names = path.attr_name.clone().map(|name| vec![name]);
+    show_attrs_of_location(&path.location_path, attr_names, shell, file)
+}
+
+fn show_attrs_of_location(
+    path: &H5Path,
+    attr_names: Option<Vec<String>>,
+    shell: &Shell,
+    file: &H5File,
+) -> CmdResult {
+    let parent_object = load_parent_object(path, shell, file)?;
+    let attr_names = collect_attributes(&parent_object, attr_names, file)?;
+    let attrs = load_attributes(&parent_object, attr_names.as_ref())?;
+    show_attrs(attr_names, attrs, shell.printer())?;
+    // The table stops loading content after an interrupt.
+    if interrupt::is_interrupted() {
+        return Err(CommandError::Interrupted);
+    }
+    Ok(CommandOutcome::KeepRunning)
+}
 
-    /// Name of attributes to print.
-    attr: Option<Vec<String>>,
+/// Return the attribute names if the arguments have the form `PATH ATTR...`.
+///
+/// This is the case if all arguments after the first are names of attributes
+/// of the first path. Otherwise, all arguments are paths.
+fn trailing_attr_names(paths: &[ObjectPath], shell: &Shell, file: &H5File) -> Option<Vec<String>> {
+    let (first, rest) = paths.split_first()?;
+    if rest.is_empty() || paths.iter().any(|path| path.attr_name.is_some()) {
+        return None;
+    }
+    let parent_object = load_parent_object(&first.location_path, shell, file).ok()?;
+    let existing = file.attr_names(&parent_object).ok()?;
+    rest.iter()
+        .map(|path| {
+            let name = path.location_path.as_raw();
+            existing
+                .iter()
+                .any(|existing| existing == name)
+                .then(|| name.to_string())
+        })
+        .collect()
 }
 
 fn show_attrs(attr_names: Vec<String>, attrs: Vec<H5Object>, printer: &Printer) -> io::Result<()> {
@@ -44,32 +115,22 @@ fn show_attrs(attr_names: Vec<String>, attrs: Vec<H5Object>, printer: &Printer)
         .map(|(name, attr)| (name.as_str(), attr))
         .collect();
     printer
-        .queue_object_table(&mut stdout(), &objects, true)
+        .queue_object_table(&mut printer.stdout(), &objects, true)
         .map(|_| ())
 }
 
-fn load_parent_object(path: &Option<H5Path>, shell: &Shell, file: &H5File) -> h5::Result<H5Object> {
-    if let Some(path) = path.as_ref() {
-        file.load(&shell.resolve_path(path))
-    } else {
-        file.load(shell.get_working_group())
-    }
+fn load_parent_object(path: &H5Path, shell: &Shell, file: &H5File) -> h5::Result<H5Object> {
+    file.load(&shell.resolve_path(path))
 }
 
 fn collect_attributes(
     parent_object: &H5Object,
     attrs: Option<Vec<String>>,
+    file: &H5File,
 ) -> h5::Result<Vec<String>> {
-    if let Some(attrs) = attrs {
-        Ok(attrs)
-    } else {
-        match parent_object {
-            H5Object::Group(group) => group.attr_names(),
-            H5Object::Dataset(dataset) => dataset.attr_names(),
-            H5Object::Attribute(_) => Err(h5::H5Error::Other(
-                "Attributes do not have attributes".into(),
-            )),
-        }
+    match attrs {
+        Some(attrs) => Ok(attrs),
+        None => file.attr_names(parent_object),
     }
 }
 
@@ -0,0 +1,148 @@
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+
+use super::cd::{is_group, resolve_group};
+use crate::bookmarks::Scope;
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::{H5File, H5Path};
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Bookmark;
+
+impl Command for Bookmark {
+    fn run(&self, args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        match args.command {
+            Action::Add { name, path, global } => {
+                check_name(&name)?;
+                let path = match path {
+                    Some(path) => resolve_group(shell, file, &path)?,
+                    None => shell.get_working_group().clone(),
+                };
+                Ok(CommandOutcome::AddBookmark {
+                    name,
+                    path,
+                    scope: scope(global),
+                })
+            }
+            Action::Ls => {
+                list(shell, file);
+                Ok(CommandOutcome::KeepRunning)
+            }
+            Action::Rm { name, global } => {
+                let scope = scope(global);
+                if !shell.bookmarks().contains(&name, scope) {
+                    return Err(CommandError::Error(format!("No such bookmark: {name}")));
+                }
+                Ok(CommandOutcome::RemoveBookmark { name, scope })
+            }
+        }
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name("bookmark"),
+    verbatim_doc_comment,
+    after_help = "Examples:
+
+Bookmark the working group and return to it later:
+  bookmark add det
+  cd :det
+
+Bookmark a group in all files that have it:
+  bookmark add --global sample /entry/sample"
+)]
+/// Manage bookmarks for groups.
+///
+/// Change into a bookmarked group with 'cd :name'.
+/// Bookmarks belong to the current file unless they are global.
+/// Global bookmarks are ignored in files that do not have the group.
+struct Arguments {
+    #[command(subcommand)]
+    command: Action,
+}
+
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Bookmark a group.
+    Add {
+        /// Name of the bookmark.
+        name: String,
+        /// Group to bookmark, defaults to the working group.
+        #[arg(value_hint = clap::ValueHint::AnyPath)]
+        path: Option<H5Path>,
+        /// Make the bookmark available in all files.
+        #[arg(short, long)]
+        global: bool,
+    },
+    /// List the bookmarks that apply to the current file.
+    #[command(visible_alias = "list")]
+    Ls,
+    /// Remove a bookmark.
+    #[command(visible_alias = "remove")]
+    Rm {
+        /// Name of the bookmark.
+        name: String,
+        /// Remove a global bookmark.
+        #[arg(short, long)]
+        global: bool,
+    },
+}
+
+fn scope(global: bool) -> Scope {
+    if global { Scope::Global } else { Scope::File }
+}
+
+fn check_name(name: &str) -> Result<(), CommandError> {
+    if name.is_empty() || name.contains(['/', ':']) || name.contains(char::is_whitespace) {
+        Err(CommandError::Error(format!(
+            "Invalid bookmark name: '{name}', names must not contain '/', ':', or spaces"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn list(shell: &Shell, file: &H5File) {
+    let bookmarks: Vec<_> = shell
+        .bookmarks()
+        .iter()
+        .filter(|(_, path, scope)| *scope == Scope::File || is_group(file, path))
+        .collect();
+    let width = bookmarks
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (name, path, scope) in bookmarks {
+        let marker = match scope {
+            Scope::File => "",
+            Scope::Global => "  (global)",
+        };
+        shell
+            .printer()
+            .println(format!("{name:<width$}  {path}{marker}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_name_rejects_paths() {
+        assert!(check_name("det").is_ok());
+        assert!(check_name("detector_1").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("entry/det").is_err());
+        assert!(check_name(":det").is_err());
+        assert!(check_name("my det").is_err());
+    }
+}
@@ -4,6 +4,7 @@ use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
 use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
 use crate::data::load_and_format_data;
 use crate::h5::{H5Dataset, H5File, H5Object, H5Path};
+use crate::interrupt;
 use crate::output::Printer;
 use crate::shell::Shell;
 
@@ -15,17 +16,22 @@ impl Command for Cat {
         let Ok(args) = Arguments::from_arg_matches(&args) else {
             return Err(CommandError::Critical("Failed to extract args".to_string()));
         };
-        let full_path = shell.resolve_path(&args.path);
-        match file.load(&full_path) {
-            Ok(object) => match object {
-                H5Object::Group(_) => Err(CommandError::Error(format!("Is a group: {full_path}"))),
-                H5Object::Dataset(dataset) => cat_dataset(dataset, shell.printer()),
+        for path in &args.paths {
+            if interrupt::is_interrupted() {
+                return Err(CommandError::Interrupted);
+            }
+            let full_path = shell.resolve_path(path);
+            match file.load(&full_path)? {
+                H5Object::Group(_) => {
+                    return Err(CommandError::Error(format!("Is a group: {full_path}")));
+                }
+                H5Object::Dataset(dataset) => cat_dataset(dataset, shell.printer())?,
                 H5Object::Attribute(_) => {
-                    Err(CommandError::Error(format!("Is an attribute: {full_path}")))
+                    return Err(CommandError::Error(format!("Is an attribute: {full_path}")));
                 }
-            },
-            Err(err) => Err(err.into()),
+            };
         }
+        Ok(CommandOutcome::KeepRunning)
     }
 
     fn arg_parser(&self) -> clap::Command {
@@ -37,8 +43,9 @@ impl Command for Cat {
 #[derive(Parser, Debug)]
 #[command(name("cat"))]
 struct Arguments {
-    /// Path of a dataset.
-    path: H5Path,
+    /// Paths of datasets.
+    #[arg(required = true, value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<H5Path>,
 }
 
 fn cat_dataset(dataset: H5Dataset, printer: &Printer) -> CmdResult {
@@ -1,5 +1,6 @@
 use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
 
+use crate::bookmarks::Scope;
 use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
 use crate::h5::{H5File, H5Object, H5Path};
 use crate::shell::Shell;
@@ -12,14 +13,17 @@ impl Command for Cd {
         let Ok(args) = Arguments::from_arg_matches(&args) else {
             return Err(CommandError::Critical("Failed to extract args".to_string()));
         };
-        let full_path = shell.resolve_path(&args.path);
-        match file.load(&full_path) {
-            Ok(object) => match object {
-                H5Object::Group(_) => Ok(CommandOutcome::ChangeWorkingGroup(full_path)),
-                _ => Err(CommandError::Error(format!("Not a group: {full_path}"))),
-            },
-            Err(err) => Err(err.into()),
+        if args.path.as_raw() == "-" {
+            let Some(previous) = shell.previous_group() else {
+                return Err(CommandError::Error("No previous group".to_string()));
+            };
+            let previous = resolve_group(shell, file, previous)?;
+            shell.printer().println(previous.as_raw());
+            return Ok(CommandOutcome::ChangeWorkingGroup(previous));
         }
+        Ok(CommandOutcome::ChangeWorkingGroup(resolve_group(
+            shell, file, &args.path,
+        )?))
     }
 
     fn arg_parser(&self) -> clap::Command {
@@ -28,9 +32,41 @@ impl Command for Cd {
 }
 
 /// Change group.
+///
+/// Use 'cd -' to return to the previous group
+/// and 'cd :name' to change into a bookmarked group.
 #[derive(Parser, Debug)]
-#[command(name("cd"))]
+#[command(name("cd"), verbatim_doc_comment)]
 struct Arguments {
-    /// Path to change into.
+    /// Path to change into, '-' for the previous group, or ':name' for a bookmark.
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
     path: H5Path,
 }
+
+/// Return the absolute path of a group or an error if `path` is not a group.
+///
+/// Paths of the form `:name` refer to bookmarks.
+pub(super) fn resolve_group(
+    shell: &Shell,
+    file: &H5File,
+    path: &H5Path,
+) -> Result<H5Path, CommandError> {
+    if let Some(name) = path.as_raw().strip_prefix(':') {
+        return match shell.bookmarks().get(name) {
+            // Global bookmarks do not apply to files that lack the group.
+            Some((path, Scope::Global)) if !is_group(file, &path) => None,
+            bookmark => bookmark,
+        }
+        .map(|(path, _)| path)
+        .ok_or_else(|| CommandError::Error(format!("No such bookmark: {name}")));
+    }
+    let full_path = shell.resolve_path(path);
+    match file.load(&full_path)? {
+        H5Object::Group(_) => Ok(full_path),
+        _ => Err(CommandError::Error(format!("Not a group: {full_path}"))),
+    }
+}
+
+pub(super) fn is_group(file: &H5File, path: &H5Path) -> bool {
+    matches!(file.load(path), Ok(H5Object::Group(_)))
+}
@@ -0,0 +1,79 @@
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::{H5File, H5Path};
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Dirs;
+
+impl Command for Dirs {
+    fn run(&self, args: ArgMatches, shell: &Shell, _file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        if args.clear {
+            return Ok(CommandOutcome::ChangeGroupStack {
+                working_group: shell.get_working_group().clone(),
+                stack: Vec::new(),
+            });
+        }
+        let groups = stack_groups(shell.get_working_group(), shell.group_stack());
+        if args.verbose {
+            for (index, group) in groups.enumerate() {
+                shell.printer().println(format!("{index:>2}  {group}"));
+            }
+        } else {
+            shell.printer().println(format_stack(groups));
+        }
+        Ok(CommandOutcome::KeepRunning)
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Show the group stack.
+///
+/// The working group is shown first, followed by the groups saved with 'pushd',
+/// most recent first.
+#[derive(Parser, Debug)]
+#[command(name("dirs"), verbatim_doc_comment)]
+struct Arguments {
+    /// Show one group per line with its position in the stack.
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Remove all groups from the stack.
+    #[arg(short, long, conflicts_with = "verbose")]
+    clear: bool,
+}
+
+/// Return the working group followed by the stack, top first.
+pub(super) fn stack_groups<'a>(
+    working_group: &'a H5Path,
+    stack: &'a [H5Path],
+) -> impl Iterator<Item = &'a H5Path> {
+    std::iter::once(working_group).chain(stack.iter().rev())
+}
+
+pub(super) fn format_stack<'a>(groups: impl Iterator<Item = &'a H5Path>) -> String {
+    groups.map(H5Path::as_raw).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_stack_top_first() {
+        let working_group = H5Path::from("/entry/sample");
+        let stack = [H5Path::root(), H5Path::from("/entry/instrument/detector")];
+        assert_eq!(
+            format_stack(stack_groups(&working_group, &stack)),
+            "/entry/sample /entry/instrument/detector /"
+        );
+    }
+}
@@ -0,0 +1,507 @@
+//! Filters that select objects by their type, dtype, shape, and size.
+//!
+//! Filters are combined like the predicates of Unix `find`:
+//! Adjacent filters must all match, `--or` separates alternatives,
+//! and `--not` negates the filter that follows it.
+//! `--not` binds stronger than `--and` which binds stronger than `--or`.
+
+use clap::{Arg, ArgAction, ArgMatches, ValueEnum, value_parser};
+use hdf5::LinkType;
+
+use crate::h5::H5Object;
+use crate::output::Printer;
+use crate::units;
+
+const HEADING: &str = "Filters";
+
+/// Return the command line arguments for filters.
+pub(super) fn args() -> [Arg; 8] {
+    [
+        Arg::new("type")
+            .long("type")
+            .value_name("TYPE")
+            .value_parser(value_parser!(ObjectType))
+            .action(ArgAction::Append)
+            .help("Match objects of this type")
+            .help_heading(HEADING),
+        Arg::new("dtype")
+            .long("dtype")
+            .value_name("DTYPE")
+            .value_parser(DtypePattern::parse)
+            .action(ArgAction::Append)
+            .help("Match datasets with this dtype, e.g., 'f64' or 'utf-8*'")
+            .help_heading(HEADING),
+        Arg::new("ndim")
+            .long("ndim")
+            .value_name("N")
+            .value_parser(Comparison::parse_count)
+            .action(ArgAction::Append)
+            .help("Match datasets with N dimensions, e.g., '2' or '>1'")
+            .help_heading(HEADING),
+        Arg::new("shape")
+            .long("shape")
+            .value_name("SHAPE")
+            .value_parser(ShapePattern::parse)
+            .action(ArgAction::Append)
+            .help("Match datasets with this shape, '*' matches any length, e.g., '*,1024'")
+            .help_heading(HEADING),
+        Arg::new("size")
+            .long("size")
+            .value_name("SIZE")
+            .value_parser(Comparison::parse_size)
+            .action(ArgAction::Append)
+            .help("Match datasets with this storage size, e.g., '>1GiB'")
+            .help_heading(HEADING),
+        operator_arg("not", "Negate the following filter"),
+        operator_arg(
+            "and",
+            "Require both the previous and the following filter (default)",
+        ),
+        operator_arg("or", "Require either the previous or the following filter"),
+    ]
+}
+
+/// An operator flag that records every occurrence so that it can be ordered
+/// relative to the filters.
+fn operator_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .num_args(0)
+        .default_missing_value("true")
+        .value_parser(value_parser!(bool))
+        .action(ArgAction::Append)
+        .help(help)
+        .help_heading(HEADING)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub(super) enum ObjectType {
+    Group,
+    Dataset,
+    /// Objects that are reached through a soft or external link.
+    Link,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Predicate {
+    Type(ObjectType),
+    Dtype(DtypePattern),
+    Ndim(Comparison),
+    Shape(ShapePattern),
+    Size(Comparison),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Filter {
+    Predicate(Predicate),
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// Build a filter from the arguments in the order they were given.
+    ///
+    /// Returns `None` if no filters were given.
+    pub(super) fn from_matches(matches: &ArgMatches) -> Result<Option<Self>, String> {
+        let mut tokens = Vec::new();
+        collect_tokens(&mut tokens, matches, "type", |t| {
+            Token::Predicate(Predicate::Type(t))
+        });
+        collect_tokens(&mut tokens, matches, "dtype", |d| {
+            Token::Predicate(Predicate::Dtype(d))
+        });
+        collect_tokens(&mut tokens, matches, "ndim", |n| {
+            Token::Predicate(Predicate::Ndim(n))
+        });
+        collect_tokens(&mut tokens, matches, "shape", |s| {
+            Token::Predicate(Predicate::Shape(s))
+        });
+        collect_tokens(&mut tokens, matches, "size", |s| {
+            Token::Predicate(Predicate::Size(s))
+        });
+        collect_tokens(&mut tokens, matches, "not", |_: bool| Token::Not);
+        collect_tokens(&mut tokens, matches, "and", |_: bool| Token::And);
+        collect_tokens(&mut tokens, matches, "or", |_: bool| Token::Or);
+        tokens.sort_by_key(|(index, _)| *index);
+        parse(tokens.into_iter().map(|(_, token)| token).collect())
+    }
+
+    pub(super) fn matches(
+        &self,
+        object: &H5Object,
+        link_type: LinkType,
+        printer: &Printer,
+    ) -> bool {
+        match self {
+            Filter::Predicate(predicate) => predicate.matches(object, link_type, printer),
+            Filter::Not(filter) => !filter.matches(object, link_type, printer),
+            Filter::And(filters) => filters
+                .iter()
+                .all(|filter| filter.matches(object, link_type, printer)),
+            Filter::Or(filters) => filters
+                .iter()
+                .any(|filter| filter.matches(object, link_type, printer)),
+        }
+    }
+}
+
+impl Predicate {
+    fn matches(&self, object: &H5Object, link_type: LinkType, printer: &Printer) -> bool {
+        if let Predicate::Type(object_type) = self {
+            return match object_type {
+                ObjectType::Group => matches!(object, H5Object::Group(_)),
+                ObjectType::Dataset => matches!(object, H5Object::Dataset(_)),
+                ObjectType::Link => link_type != LinkType::Hard,
+            };
+        }
+        // All other predicates only apply to datasets.
+        let H5Object::Dataset(dataset) = object else {
+            return false;
+        };
+        let dataset = dataset.underlying();
+        match self {
+            Predicate::Type(_) => unreachable!(),
+            Predicate::Dtype(pattern) => dataset
+                .dtype()
+                .and_then(|dtype| dtype.to_descriptor())
+                .is_ok_and(|descriptor| {
+                    let bump = bumpalo::Bump::new();
+                    pattern.matches(&printer.format_dtype(&descriptor, &bump))
+                }),
+            Predicate::Ndim(comparison) => comparison.matches(dataset.ndim() as u64),
+            Predicate::Shape(pattern) => pattern.matches(&dataset.shape()),
+            Predicate::Size(comparison) => comparison.matches(dataset.storage_size()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Predicate(Predicate),
+    Not,
+    And,
+    Or,
+}
+
+fn collect_tokens<T: Clone + Send + Sync + 'static>(
+    tokens: &mut Vec<(usize, Token)>,
+    matches: &ArgMatches,
+    id: &str,
+    to_token: impl Fn(T) -> Token,
+) {
+    if let (Some(indices), Some(values)) = (matches.indices_of(id), matches.get_many::<T>(id)) {
+        tokens.extend(indices.zip(values.cloned().map(to_token)));
+    }
+}
+
+fn parse(tokens: Vec<Token>) -> Result<Option<Filter>, String> {
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+    let mut alternatives = Vec::new();
+    let mut conjunction = Vec::new();
+    let mut negate = false;
+    // Whether an operand must follow, i.e., at the start or after --and or --or.
+    let mut expect_operand = true;
+    for token in tokens {
+        match token {
+            Token::Predicate(predicate) => {
+                let mut filter = Filter::Predicate(predicate);
+                if negate {
+                    filter = Filter::Not(Box::new(filter));
+                    negate = false;
+                }
+                conjunction.push(filter);
+                expect_operand = false;
+            }
+            Token::Not => {
+                negate = !negate;
+                expect_operand = true;
+            }
+            Token::And | Token::Or if expect_operand => {
+                let name = if token == Token::And { "and" } else { "or" };
+                return Err(format!("Expected a filter before --{name}"));
+            }
+            Token::And => {
+                expect_operand = true;
+            }
+            Token::Or => {
+                alternatives.push(combine(std::mem::take(&mut conjunction), Filter::And));
+                expect_operand = true;
+            }
+        }
+    }
+    if expect_operand {
+        return Err("Expected a filter after the last operator".to_string());
+    }
+    alternatives.push(combine(conjunction, Filter::And));
+    Ok(Some(combine(alternatives, Filter::Or)))
+}
+
+fn combine(mut filters: Vec<Filter>, make: fn(Vec<Filter>) -> Filter) -> Filter {
+    if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        make(filters)
+    }
+}
+
+/// A pattern for dtype names as shown by `ls -l`, where '*' matches any characters.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct DtypePattern(String);
+
+impl DtypePattern {
+    fn parse(s: &str) -> Result<Self, String> {
+        if s.is_empty() {
+            Err("Empty dtype".to_string())
+        } else {
+            Ok(Self(s.to_lowercase()))
+        }
+    }
+
+    fn matches(&self, dtype: &str) -> bool {
+        let dtype = dtype.to_lowercase();
+        let mut parts = self.0.split('*');
+        let first = parts.next().unwrap_or("");
+        let Some(mut rest) = dtype.strip_prefix(first) else {
+            return false;
+        };
+        let mut parts: Vec<_> = parts.collect();
+        let Some(last) = parts.pop() else {
+            // No wildcard
+            return rest.is_empty();
+        };
+        for part in parts {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+        rest.ends_with(last)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Operator {
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    GreaterEqual,
+    Greater,
+}
+
+impl Operator {
+    /// Return whether `value <operator> reference` holds.
+    pub(super) fn apply<T: PartialOrd>(self, value: T, reference: T) -> bool {
+        match self {
+            Operator::Less => value < reference,
+            Operator::LessEqual => value <= reference,
+            Operator::Equal => value == reference,
+            Operator::NotEqual => value != reference,
+            Operator::GreaterEqual => value >= reference,
+            Operator::Greater => value > reference,
+        }
+    }
+}
+
+/// Compares a number against a value, e.g., '>10' or '<=2'.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct Comparison {
+    operator: Operator,
+    value: u64,
+}
+
+impl Comparison {
+    fn parse_count(s: &str) -> Result<Self, String> {
+        let (operator, value) = split_operator(s);
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid number: '{}'", value.trim()))?;
+        Ok(Self { operator, value })
+    }
+
+    /// Parse a size with an optional unit, e.g., '>1.5GiB', see [`units::parse_size`].
+    fn parse_size(s: &str) -> Result<Self, String> {
+        let (operator, size) = split_operator(s);
+        Ok(Self {
+            operator,
+            value: units::parse_size(size)?,
+        })
+    }
+
+    fn matches(&self, value: u64) -> bool {
+        self.operator.apply(value, self.value)
+    }
+}
+
+/// Split a leading comparison operator off `s`, the operator defaults to equality.
+pub(super) fn split_operator(s: &str) -> (Operator, &str) {
+    let s = s.trim_start();
+    for (prefix, operator) in [
+        ("<=", Operator::LessEqual),
+        (">=", Operator::GreaterEqual),
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+        ("=", Operator::Equal),
+    ] {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            return (operator, rest);
+        }
+    }
+    (Operator::Equal, s)
+}
+
+/// A pattern for dataset shapes, e.g., '*,1024' where '*' matches any length.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct ShapePattern(Vec<Option<usize>>);
+
+impl ShapePattern {
+    fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let s = s
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .or_else(|| s.strip_prefix('[').and_then(|s| s.strip_suffix(']')))
+            .unwrap_or(s)
+            .trim();
+        if s.is_empty() {
+            // A scalar
+            return Ok(Self(Vec::new()));
+        }
+        s.split(',')
+            .map(|length| match length.trim() {
+                "*" => Ok(None),
+                length => length
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("Invalid length in shape: '{length}'")),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    fn matches(&self, shape: &[usize]) -> bool {
+        self.0.len() == shape.len()
+            && self
+                .0
+                .iter()
+                .zip(shape)
+                .all(|(pattern, length)| pattern.is_none_or(|pattern| pattern == *length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse_filter(args: &[&str]) -> Result<Option<Filter>, String> {
+        let matches = clap::Command::new("find")
+            .no_binary_name(true)
+            .args(super::args())
+            .try_get_matches_from(args)
+            .unwrap();
+        Filter::from_matches(&matches)
+    }
+
+    fn dtype(pattern: &str) -> Filter {
+        Filter::Predicate(Predicate::Dtype(DtypePattern::parse(pattern).unwrap()))
+    }
+
+    fn object_type(object_type: ObjectType) -> Filter {
+        Filter::Predicate(Predicate::Type(object_type))
+    }
+
+    #[test]
+    fn no_filters() {
+        assert_eq!(parse_filter(&[]), Ok(None));
+    }
+
+    #[test]
+    fn filters_are_combined_with_and() {
+        assert_eq!(
+            parse_filter(&["--type", "dataset", "--dtype", "f64"]),
+            Ok(Some(Filter::And(vec![
+                object_type(ObjectType::Dataset),
+                dtype("f64")
+            ])))
+        );
+        assert_eq!(
+            parse_filter(&["--type", "dataset", "--and", "--dtype", "f64"]),
+            parse_filter(&["--type", "dataset", "--dtype", "f64"]),
+        );
+    }
+
+    #[test]
+    fn not_binds_stronger_than_and_and_or() {
+        assert_eq!(
+            parse_filter(&[
+                "--dtype", "f64", "--or", "--not", "--type", "group", "--dtype", "f32"
+            ]),
+            Ok(Some(Filter::Or(vec![
+                dtype("f64"),
+                Filter::And(vec![
+                    Filter::Not(Box::new(object_type(ObjectType::Group))),
+                    dtype("f32")
+                ])
+            ])))
+        );
+    }
+
+    #[test]
+    fn operator_without_operand() {
+        assert!(parse_filter(&["--or", "--dtype", "f64"]).is_err());
+        assert!(parse_filter(&["--dtype", "f64", "--and"]).is_err());
+        assert!(parse_filter(&["--dtype", "f64", "--not"]).is_err());
+        assert!(parse_filter(&["--dtype", "f64", "--or", "--and", "--ndim", "1"]).is_err());
+    }
+
+    #[test]
+    fn dtype_pattern() {
+        let pattern = DtypePattern::parse("f64").unwrap();
+        assert!(pattern.matches("f64"));
+        assert!(!pattern.matches("f32"));
+        let pattern = DtypePattern::parse("utf-8*").unwrap();
+        assert!(pattern.matches("utf-8"));
+        assert!(pattern.matches("utf-8(12)"));
+        assert!(!pattern.matches("ascii(12)"));
+        let pattern = DtypePattern::parse("*(*)").unwrap();
+        assert!(pattern.matches("ascii(6)"));
+        assert!(!pattern.matches("ascii"));
+    }
+
+    #[test]
+    fn parse_count() {
+        assert_eq!(
+            Comparison::parse_count("2"),
+            Ok(Comparison {
+                operator: Operator::Equal,
+                value: 2
+            })
+        );
+        assert_eq!(
+            Comparison::parse_count(">=1"),
+            Ok(Comparison {
+                operator: Operator::GreaterEqual,
+                value: 1
+            })
+        );
+        assert!(Comparison::parse_count(">x").is_err());
+    }
+
+    #[test]
+    fn shape_pattern() {
+        let pattern = ShapePattern::parse("*,1024").unwrap();
+        assert!(pattern.matches(&[3, 1024]));
+        assert!(!pattern.matches(&[3, 1023]));
+        assert!(!pattern.matches(&[1024]));
+        assert!(ShapePattern::parse("(2, 3)").unwrap().matches(&[2, 3]));
+        assert!(ShapePattern::parse("()").unwrap().matches(&[]));
+        assert!(ShapePattern::parse("2,x").is_err());
+    }
+}
@@ -0,0 +1,1127 @@
+use super::foreach::build_inputs;
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::data::load_and_format_data;
+use crate::h5::{
+    self, H5File, H5Group, H5Object, H5Path,
+    traverse::{self, Node, Visit, WalkOptions},
+};
+use crate::interrupt;
+use crate::output::{
+    Printer, Progress,
+    style::{DATASET_CHARACTER, GROUP_CHARACTER},
+};
+use crate::shell::Shell;
+use bumpalo::{Bump, collections::String as BumpString};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use crossterm::{ExecutableCommand, QueueableCommand, style::Print};
+use filter::Filter;
+use hdf5::LinkType;
+use regex::{Match, Regex};
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::{ControlFlow, Range};
+use std::str::FromStr;
+use value::ValueQuery;
+
+mod filter;
+mod value;
+
+#[derive(Clone, Copy, Default)]
+pub struct Find;
+
+impl Command for Find {
+    fn run(&self, args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let filter = Filter::from_matches(&args).map_err(CommandError::Error)?;
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        let absolute_target = shell.resolve_path(&args.target);
+        let target = file.load(&absolute_target)?;
+        if let H5Object::Attribute(_) = target {
+            return Err(CommandError::Error("Is an attribute".to_string()));
+        }
+
+        if let (Some(Pattern::Attr { value: Some(_), .. }), Some(_)) = (
+            &args.pattern,
+            args.value.as_ref().or(args.contains.as_ref()),
+        ) {
+            return Err(CommandError::Error(
+                "Use either an attribute value pattern or --value/--contains".to_string(),
+            ));
+        }
+
+        // The target itself is only a candidate when searching for attributes
+        // or when it is a dataset.
+        let include_target = matches!(args.pattern, Some(Pattern::Attr { .. }))
+            || matches!(target, H5Object::Dataset(_));
+        let max_depth = if args.non_recursive {
+            Some(args.max_depth.map_or(1, |max| max.min(1)))
+        } else {
+            args.max_depth
+        };
+        let output = Output::from_args(&args);
+        let mut search = Search {
+            pattern: args.pattern,
+            filter,
+            min_depth: args.min_depth.unwrap_or(if include_target { 0 } else { 1 }),
+            max_depth,
+            follow_links: args.follow_links,
+            value: args.value.or(args.contains),
+            output,
+            found: Vec::new(),
+            count: 0,
+            failed: Vec::new(),
+            shown: HashMap::new(),
+            printer: shell.printer(),
+        };
+
+        let mut output = shell.printer().progress("Searching…");
+        if search.min_depth == 0 {
+            search.visit(
+                &mut output,
+                &target,
+                LinkType::Hard,
+                &H5Path::from("."),
+                args.target.as_raw(),
+            )?;
+        }
+        if let H5Object::Group(group) = &target
+            && search.max_depth != Some(0)
+        {
+            search.walk(&mut output, group, &args.target)?;
+        }
+        let mut stdout = output.finish();
+        for message in &search.failed {
+            shell.printer().print_shell_error(message);
+        }
+        // Value searches stop reading early without an error.
+        if interrupt::is_interrupted() {
+            return Err(CommandError::Interrupted);
+        }
+        search.finish(&mut stdout)
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command().args(filter::args())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name("find"),
+    verbatim_doc_comment,
+    after_help = "Examples:
+
+Find all locations with 'monitor' in their name:
+  find monitor
+
+Find all locations that have an attr with 'tag' in its name:
+  find @tag
+
+Find all locations that have an attr with 'NX_class' in its name
+and a value that matches 'NXmonitor':
+  find @NX_class=NXmonitor
+
+Find all locations that are named exactly 'sample':
+  find ^sample$
+
+Find all 2d float datasets with 1024 columns:
+  find --dtype 'f*' --shape '*,1024'
+
+Find all datasets that are larger than 1GiB or are not integers:
+  find --type dataset --size '>1GiB' --or --not --dtype 'i*'
+
+Find all groups in 'entry' and its children, '.' matches any name:
+  find --type group --maxdepth 2 . entry
+
+Find datasets named 'data' and also search in groups behind soft links:
+  find -L ^data$
+
+Find datasets and attributes with values above 1e6 or with NaNs:
+  find --value 'x > 1e6'
+  find --contains NaN
+
+Find 'units' attributes that contain 'mm':
+  find @units --contains mm
+
+Inspect all 1d datasets:
+  find --ndim 1 --exec 'inspect {}'
+
+Count the datasets and show their sizes:
+  find --type dataset --count
+  find --type dataset --long"
+)]
+/// Find datasets and groups.
+///
+/// The pattern is a regex, and the implementation searches for matches
+/// in dataset, group, attribute names.
+///
+/// Filters select objects by their type, dtype, shape, and size.
+/// Adjacent filters must all match, '--or' separates alternatives,
+/// and '--not' negates the following filter.
+/// Without a pattern, all objects that pass the filters are shown.
+///
+/// '--value' and '--contains' search the values of datasets and attributes
+/// and show the index of the first match.
+///
+/// Every object is shown only once, other links to it are shown as aliases.
+/// Soft links are shown as separate objects unless they are followed with '-L'.
+/// Groups behind soft links are only searched with '-L'.
+/// Links back to the searched group are shown but not searched again.
+struct Arguments {
+    /// Pattern to search for.
+    pattern: Option<Pattern>,
+
+    /// Search at this path.
+    #[arg(default_value = ".", value_hint = clap::ValueHint::AnyPath)]
+    target: H5Path,
+
+    /// Do not search groups recursively, same as '--maxdepth 1'.
+    #[arg(short = 'R', long = "nr", default_value_t = false)]
+    non_recursive: bool,
+
+    /// Descend at most N levels below the search path.
+    #[arg(long = "maxdepth", value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Only show objects at least N levels below the search path.
+    #[arg(long = "mindepth", value_name = "N")]
+    min_depth: Option<usize>,
+
+    /// Follow soft links into the groups they point to, external links are never followed.
+    #[arg(short = 'L', long = "follow")]
+    follow_links: bool,
+
+    /// Search for numbers that compare to a value, e.g., 'x > 1e6' or 'x != 0'.
+    #[arg(long, value_name = "EXPR", value_parser = ValueQuery::parse_comparison)]
+    value: Option<ValueQuery>,
+
+    /// Search for NaN, inf, +inf, -inf in numbers, or for a regex in strings.
+    /// The keywords are not used as regexes.
+    #[arg(
+        long,
+        value_name = "VALUE",
+        value_parser = ValueQuery::parse_contains,
+        conflicts_with = "value"
+    )]
+    contains: Option<ValueQuery>,
+
+    /// Run an h5sh command for each match, '{}' is replaced by the path.
+    #[arg(short = 'x', long, value_name = "COMMAND", group = "output")]
+    exec: Option<String>,
+
+    /// Show only the paths without styling, one per line.
+    #[arg(long, group = "output")]
+    plain: bool,
+
+    /// Show only the paths without styling, separated by NUL characters.
+    #[arg(short = '0', long, group = "output")]
+    print0: bool,
+
+    /// Show only the number of matches.
+    #[arg(short = 'c', long, group = "output")]
+    count: bool,
+
+    /// Show the matches in a table like 'ls -l'.
+    #[arg(short = 'l', long, group = "output")]
+    long: bool,
+}
+
+/// How matches are shown.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Output {
+    /// Paths with the matched part highlighted and matching attributes.
+    Highlighted,
+    Plain,
+    Print0,
+    Count,
+    Long,
+    Exec(String),
+}
+
+impl Output {
+    fn from_args(args: &Arguments) -> Self {
+        if let Some(command) = &args.exec {
+            Output::Exec(command.clone())
+        } else if args.plain {
+            Output::Plain
+        } else if args.print0 {
+            Output::Print0
+        } else if args.count {
+            Output::Count
+        } else if args.long {
+            Output::Long
+        } else {
+            Output::Highlighted
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    Name(Regex),
+    Attr { name: Regex, value: Option<Regex> },
+}
+
+struct Search<'p> {
+    pattern: Option<Pattern>,
+    filter: Option<Filter>,
+    min_depth: usize,
+    max_depth: Option<usize>,
+    follow_links: bool,
+    value: Option<ValueQuery>,
+    output: Output,
+    /// Matches that are shown after the search.
+    found: Vec<(H5Path, H5Object)>,
+    count: usize,
+    /// Objects whose values could not be searched.
+    failed: Vec<String>,
+    /// Paths relative to the search path of the objects that were shown
+    /// so that later links to them can be shown as aliases.
+    shown: HashMap<String, hdf5::LocationType>,
+    printer: &'p Printer,
+}
+
+impl Search<'_> {
+    /// Visit all objects below `group` as they are found.
+    ///
+    /// `target` is the path of `group` as it is shown in the output.
+    fn walk(
+        &mut self,
+        output: &mut Progress<impl Write>,
+        group: &H5Group,
+        target: &H5Path,
+    ) -> CmdResult {
+        let options = WalkOptions {
+            max_depth: self.max_depth,
+            follow_links: self.follow_links,
+        };
+        let flow = traverse::walk(group, options, |visit| {
+            check_interrupt()?;
+            output.tick();
+            let result = match visit {
+                Visit::Object(node) => self.visit_node(output, group, target, node),
+                Visit::Alias { path, node } => self.visit_alias(output, target, path, node),
+                // Show the link but do not search the group again.
+                Visit::Cycle(node) => self.visit_node(output, group, target, node),
+            };
+            match result {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(err) => ControlFlow::Break(err),
+            }
+        })?;
+        match flow {
+            ControlFlow::Continue(()) => Ok(CommandOutcome::KeepRunning),
+            ControlFlow::Break(err) => Err(err),
+        }
+    }
+
+    fn visit_node(
+        &mut self,
+        queue: &mut dyn Write,
+        group: &H5Group,
+        target: &H5Path,
+        node: &Node,
+    ) -> Result<(), CommandError> {
+        if node.depth < self.min_depth {
+            return Ok(());
+        }
+        // Skip objects that cannot be loaded, e.g., dangling soft links.
+        let Ok(object) = group.load_descendant(&node.path) else {
+            return Ok(());
+        };
+        let (parent, name) = match node.split() {
+            ("", name) => (target.clone(), name),
+            (parent, name) => (child_path(target, parent), name),
+        };
+        if self.visit(queue, &object, node.link_type, &parent, name)?
+            && self.output == Output::Highlighted
+        {
+            self.shown.insert(node.path.clone(), object.location_type());
+        }
+        Ok(())
+    }
+
+    /// Show another link to an object if the object was shown.
+    fn visit_alias(
+        &mut self,
+        queue: &mut dyn Write,
+        target: &H5Path,
+        path: &str,
+        node: &Node,
+    ) -> Result<(), CommandError> {
+        if let Some(&location_type) = self.shown.get(&node.path) {
+            write_alias(
+                queue,
+                &child_path(target, path),
+                &child_path(target, &node.path),
+                location_type,
+                self.printer,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Show or record `object` if it matches the filters and pattern.
+    ///
+    /// Returns whether the object matched.
+    fn visit(
+        &mut self,
+        queue: &mut dyn Write,
+        object: &H5Object,
+        link_type: LinkType,
+        parent: &H5Path,
+        name: &str,
+    ) -> Result<bool, CommandError> {
+        if self
+            .filter
+            .as_ref()
+            .is_some_and(|filter| !filter.matches(object, link_type, self.printer))
+        {
+            return Ok(false);
+        }
+        let highlight = match &self.pattern {
+            Some(Pattern::Name(pattern)) => match pattern.find(name) {
+                Some(mat) => Some(mat.range()),
+                None => return Ok(false),
+            },
+            _ => None,
+        };
+        let (location, dataset): (&hdf5::Location, _) = match object {
+            H5Object::Group(group) => (group.underlying(), None),
+            H5Object::Dataset(dataset) => (dataset.underlying(), Some(dataset.underlying())),
+            H5Object::Attribute(_) => {
+                return Err(CommandError::Error("Is an attribute".to_string()));
+            }
+        };
+        let path = child_path(parent, name);
+        let mut sink = std::io::sink();
+        // Details are only shown in the default output.
+        let details: &mut dyn Write = if self.output == Output::Highlighted {
+            &mut *queue
+        } else {
+            &mut sink
+        };
+        let found = match (&self.pattern, &self.value) {
+            (_, Some(query)) => {
+                // Only search attributes when looking for attributes by name.
+                let (dataset, key) = match &self.pattern {
+                    Some(Pattern::Attr { name: key, .. }) => (None, Some(key)),
+                    _ => (dataset, None),
+                };
+                // Objects that cannot be read do not stop the search.
+                match find_values(location, dataset, key, query) {
+                    Ok(matches) => {
+                        write_value_matches(details, location, &path, matches, self.printer)?
+                    }
+                    Err(err) => {
+                        self.failed
+                            .push(format!("Failed to search the values of {path}: {err}"));
+                        false
+                    }
+                }
+            }
+            (Some(Pattern::Attr { name: key, value }), None) => {
+                find_attr_in_location(details, location, &path, key, value, self.printer)?
+            }
+            (_, None) => {
+                write_matched_path(
+                    details,
+                    parent,
+                    &name.into(),
+                    highlight,
+                    object.location_type(),
+                    self.printer,
+                )?;
+                true
+            }
+        };
+        if found {
+            self.record(queue, path, object)?;
+        }
+        Ok(found)
+    }
+
+    fn record(&mut self, queue: &mut dyn Write, path: H5Path, object: &H5Object) -> CmdResult {
+        match self.output {
+            Output::Highlighted => {}
+            Output::Plain => {
+                queue.queue(Print(path))?.queue(Print('\n'))?;
+            }
+            Output::Print0 => {
+                queue.queue(Print(path))?.queue(Print('\0'))?;
+            }
+            Output::Count => self.count += 1,
+            Output::Long | Output::Exec(_) => self.found.push((path, object.clone())),
+        }
+        Ok(CommandOutcome::KeepRunning)
+    }
+
+    /// Show the recorded matches or run the command for them.
+    fn finish(self, queue: &mut dyn Write) -> CmdResult {
+        match self.output {
+            Output::Count => {
+                queue.queue(Print(self.count))?.queue(Print('\n'))?;
+            }
+            Output::Long => {
+                let objects: Vec<_> = self
+                    .found
+                    .iter()
+                    .map(|(path, object)| (path.as_raw(), object))
+                    .collect();
+                self.printer
+                    .queue_object_table(&mut &mut *queue, &objects, true)?;
+            }
+            Output::Exec(command) => {
+                queue.flush()?;
+                let paths: Vec<_> = self
+                    .found
+                    .into_iter()
+                    .map(|(path, _)| path.as_raw().to_string())
+                    .collect();
+                return Ok(CommandOutcome::Foreach {
+                    inputs: build_inputs(&paths, &command),
+                    stop_on_error: false,
+                });
+            }
+            Output::Highlighted | Output::Plain | Output::Print0 => {}
+        }
+        queue.flush()?;
+        Ok(CommandOutcome::KeepRunning)
+    }
+}
+
+fn check_interrupt() -> ControlFlow<CommandError> {
+    if interrupt::is_interrupted() {
+        ControlFlow::Break(CommandError::Interrupted)
+    } else {
+        ControlFlow::Continue(())
+    }
+}
+
+fn child_path(parent: &H5Path, name: &str) -> H5Path {
+    if parent.is_current() {
+        H5Path::from(name)
+    } else {
+        parent.join(&H5Path::from(name))
+    }
+}
+
+fn write_matched_path<'q, Q: QueueableCommand + ?Sized>(
+    queue: &'q mut Q,
+    target: &H5Path,
+    path: &H5Path,
+    highlight: Option<Range<usize>>,
+    location_type: hdf5::LocationType,
+    printer: &Printer,
+) -> std::io::Result<&'q mut Q> {
+    let base_style = if !target.is_current() {
+        queue.queue(&printer.style().group)?.queue(Print(target))?;
+        if !target.as_raw().ends_with('/') {
+            queue.queue(Print('/'))?;
+        }
+
+        if location_type == hdf5::LocationType::Dataset {
+            // Switch to the dataset style
+            queue.queue(printer.style().reset())?;
+            &printer.style().dataset
+        } else {
+            &printer.style().group
+        }
+    } else {
+        match location_type {
+            hdf5::LocationType::Group => &printer.style().group,
+            _ => &printer.style().dataset,
+        }
+    };
+
+    queue.queue(base_style)?;
+    if let Some(range) = highlight {
+        queue_with_highlight_range(queue, path.as_raw(), range, printer, Some(base_style))?;
+    } else {
+        queue.queue(Print(path))?;
+    }
+    queue.queue(printer.style().reset())?;
+
+    let character = match location_type {
+        hdf5::LocationType::Dataset => DATASET_CHARACTER,
+        hdf5::LocationType::Group => GROUP_CHARACTER,
+        _ => None,
+    };
+    if let Some(character) = character {
+        queue.queue(Print(character))?;
+    }
+
+    queue.queue(Print("\n"))
+}
+
+/// Show another path that leads to an object that was shown before.
+fn write_alias<Q: QueueableCommand + ?Sized>(
+    queue: &mut Q,
+    alias: &H5Path,
+    original: &H5Path,
+    location_type: hdf5::LocationType,
+    printer: &Printer,
+) -> std::io::Result<()> {
+    let style = match location_type {
+        hdf5::LocationType::Group => &printer.style().group,
+        _ => &printer.style().dataset,
+    };
+    queue
+        .queue(style)?
+        .queue(Print(alias))?
+        .queue(printer.style().reset())?
+        .queue(Print("\n  alias of: "))?
+        .queue(style)?
+        .queue(Print(original))?
+        .queue(printer.style().reset())?
+        .queue(Print('\n'))?;
+    Ok(())
+}
+
+/// Show the attributes of a location that match, returns whether there were any.
+fn find_attr_in_location<Q: QueueableCommand + ?Sized>(
+    queue: &mut Q,
+    location: &hdf5::Location,
+    target: &H5Path,
+    key: &Regex,
+    value: &Option<Regex>,
+    printer: &Printer,
+) -> Result<bool, CommandError> {
+    let mut buffer: Vec<u8> = Vec::new();
+    for attr_name in location.attr_names()? {
+        match_attr(&mut buffer, location, &attr_name, key, value, printer)?;
+    }
+    let found = !buffer.is_empty();
+    if found {
+        let bump = Bump::new();
+        queue
+            .queue(Print(printer.format_location_path(target, location, &bump)))?
+            .queue(Print('\n'))?
+            .queue(Print(String::from_utf8(buffer).unwrap_or_default()))?;
+    }
+    Ok(found)
+}
+
+/// Indices of the first matching values in a dataset and its attributes.
+struct ValueMatches {
+    dataset: Option<Vec<usize>>,
+    attrs: Vec<(String, Vec<usize>)>,
+}
+
+/// Search the values of a dataset and of the attributes of a location.
+fn find_values(
+    location: &hdf5::Location,
+    dataset: Option<&hdf5::Dataset>,
+    key: Option<&Regex>,
+    query: &ValueQuery,
+) -> h5::Result<ValueMatches> {
+    let dataset = match dataset {
+        Some(dataset) => query.find_in_dataset(dataset)?,
+        None => None,
+    };
+    let mut attrs = Vec::new();
+    for attr_name in location.attr_names()? {
+        if key.is_some_and(|key| !key.is_match(&attr_name)) {
+            continue;
+        }
+        if let Some(index) = query.find_in_attribute(&location.attr(&attr_name)?)? {
+            attrs.push((attr_name, index));
+        }
+    }
+    Ok(ValueMatches { dataset, attrs })
+}
+
+/// Show the index of the first match in the dataset and in every matching attribute
+/// and return whether there were any matches.
+fn write_value_matches<Q: QueueableCommand + ?Sized>(
+    queue: &mut Q,
+    location: &hdf5::Location,
+    target: &H5Path,
+    matches: ValueMatches,
+    printer: &Printer,
+) -> Result<bool, CommandError> {
+    let ValueMatches {
+        dataset: dataset_match,
+        attrs: attr_matches,
+    } = matches;
+    if dataset_match.is_none() && attr_matches.is_empty() {
+        return Ok(false);
+    }
+
+    let bump = Bump::new();
+    queue.queue(Print(printer.format_location_path(target, location, &bump)))?;
+    if let Some(index) = dataset_match {
+        queue_index(queue.queue(Print("  "))?, &index, printer)?;
+    }
+    queue.queue(Print('\n'))?;
+    for (attr_name, index) in attr_matches {
+        queue
+            .queue(Print("  "))?
+            .queue(&printer.style().attribute)?
+            .queue(Print(attr_name))?
+            .queue(printer.style().reset())?
+            .queue(Print("  "))?;
+        queue_index(queue, &index, printer)?.queue(Print('\n'))?;
+    }
+    Ok(true)
+}
+
+fn queue_index<'q, Q: QueueableCommand + ?Sized>(
+    queue: &'q mut Q,
+    index: &[usize],
+    printer: &Printer,
+) -> std::io::Result<&'q mut Q> {
+    queue
+        .queue(&printer.style().shape)?
+        .queue(Print(format!("{index:?}")))?
+        .queue(printer.style().reset())
+}
+
+fn match_attr<E: ExecutableCommand + QueueableCommand>(
+    buffer: &mut E,
+    location: &hdf5::Location,
+    attr_name: &str,
+    key_pattern: &Regex,
+    value_pattern: &Option<Regex>,
+    printer: &Printer,
+) -> CmdResult {
+    let Some(key_match) = key_pattern.find(attr_name) else {
+        return Ok(CommandOutcome::KeepRunning);
+    };
+
+    let bump = Bump::new();
+
+    let value = load_and_format_data(&location.attr(attr_name)?, None, None, printer, &bump)
+        .unwrap_or_else(|err| {
+            use std::fmt::Write;
+            let mut out = BumpString::new_in(&bump);
+            let _ = write!(out, "Failed to load attribute: {err}");
+            out
+        });
+
+    match value_pattern {
+        Some(value_pattern) => {
+            let Some(value_match) = value_pattern.find(&value) else {
+                return Ok(CommandOutcome::KeepRunning);
+            };
+            write_attr_match(
+                buffer,
+                attr_name,
+                &value,
+                key_match,
+                Some(value_match),
+                printer,
+            )?;
+        }
+        None => {
+            write_attr_match(buffer, attr_name, &value, key_match, None, printer)?;
+        }
+    }
+    Ok(CommandOutcome::KeepRunning)
+}
+
+fn write_attr_match<'e, Q: QueueableCommand>(
+    queue: &'e mut Q,
+    attr_name: &str,
+    attr_value: &str,
+    key_match: Match,
+    value_match: Option<Match>,
+    printer: &Printer,
+) -> std::io::Result<&'e mut Q> {
+    let base_style = &printer.style().attribute;
+    queue.queue(Print("  "))?.queue(base_style)?;
+    queue_with_highlight_range(
+        queue,
+        attr_name,
+        key_match.range(),
+        printer,
+        Some(base_style),
+    )?;
+    queue.queue(printer.style().reset())?.queue(Print(" = "))?;
+
+    if let Some(mat) = value_match {
+        queue_with_highlight_range(queue, attr_value, mat.range(), printer, None)?;
+    } else {
+        queue.queue(Print(attr_value))?;
+    }
+
+    queue.queue(Print('\n'))
+}
+
+fn queue_with_highlight_range<'q, Q: QueueableCommand + ?Sized>(
+    queue: &'q mut Q,
+    string: &str,
+    range: Range<usize>,
+    printer: &Printer,
+    base_style: Option<&crate::output::style::Item>,
+) -> std::io::Result<&'q mut Q> {
+    queue
+        .queue(Print(&string[..range.start]))?
+        .queue(&printer.style().emphasis)?
+        .queue(Print(&string[range.clone()]))?
+        .queue(printer.style().reset())?;
+    if let Some(base_style) = base_style {
+        queue.queue(base_style)?;
+    }
+    queue.queue(Print(&string[range.end..]))
+}
+
+impl FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(s) = s.strip_prefix('@') {
+            let mut parts = s.splitn(2, '=');
+            let name = Regex::new(parts.next().ok_or_else(|| anyhow::anyhow!("Bad pattern"))?)?;
+
+            if let Some(value) = parts.next() {
+                Ok(Pattern::Attr {
+                    name,
+                    value: Some(Regex::new(value)?),
+                })
+            } else {
+                Ok(Pattern::Attr { name, value: None })
+            }
+        } else {
+            Ok(Pattern::Name(Regex::new(s)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod parser {
+        use super::super::*;
+
+        fn parse_args(args: &[&str]) -> Arguments {
+            Arguments::from_arg_matches(
+                &Find
+                    .arg_parser()
+                    .no_binary_name(true)
+                    .try_get_matches_from(args)
+                    .unwrap(),
+            )
+            .unwrap()
+        }
+
+        fn assert_pattern_name(pattern: &Option<Pattern>, expected: &str) {
+            match pattern {
+                Some(Pattern::Name(name)) => {
+                    assert_eq!(name.as_str(), expected);
+                }
+                _ => {
+                    panic!("expected a Name pattern, got Attr")
+                }
+            }
+        }
+
+        fn assert_pattern_attr_key(pattern: &Option<Pattern>, expected: &str) {
+            match pattern {
+                Some(Pattern::Attr { name, value }) => {
+                    assert_eq!(name.as_str(), expected);
+                    assert!(value.is_none());
+                }
+                _ => {
+                    panic!("expected an Attr pattern, got Name")
+                }
+            }
+        }
+
+        fn assert_pattern_attr_key_value(
+            pattern: &Option<Pattern>,
+            expected_name: &str,
+            expected_value: &str,
+        ) {
+            match pattern {
+                Some(Pattern::Attr { name, value }) => {
+                    assert_eq!(name.as_str(), expected_name);
+                    assert_eq!(value.as_ref().unwrap().as_str(), expected_value);
+                }
+                _ => {
+                    panic!("expected an Attr pattern, got Name")
+                }
+            }
+        }
+
+        #[test]
+        fn parse_pattern_name() {
+            let args = parse_args(&["some_name"]);
+            assert_pattern_name(&args.pattern, "some_name");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_name_with_target() {
+            let args = parse_args(&["needle", "farm/barn"]);
+            assert_pattern_name(&args.pattern, "needle");
+            assert_eq!(args.target, H5Path::from("farm/barn"));
+        }
+
+        #[test]
+        fn parse_pattern_name_with_equal() {
+            let args = parse_args(&["a=b"]);
+            assert_pattern_name(&args.pattern, "a=b");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_name_with_trailing_equal() {
+            let args = parse_args(&["name="]);
+            assert_pattern_name(&args.pattern, "name=");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_name() {
+            let args = parse_args(&["@key"]);
+            assert_pattern_attr_key(&args.pattern, "key");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_with_value() {
+            let args = parse_args(&["@abc=2hs"]);
+            assert_pattern_attr_key_value(&args.pattern, "abc", "2hs");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_with_value_extra_equal() {
+            let args = parse_args(&["@asd=qwe=zxc"]);
+            assert_pattern_attr_key_value(&args.pattern, "asd", "qwe=zxc");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_with_value_trailing_equal() {
+            let args = parse_args(&["@asd=qwe="]);
+            assert_pattern_attr_key_value(&args.pattern, "asd", "qwe=");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_with_empty_value() {
+            let args = parse_args(&["@abc="]);
+            assert_pattern_attr_key_value(&args.pattern, "abc", "");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_without_key() {
+            let args = parse_args(&["@=value"]);
+            assert_pattern_attr_key_value(&args.pattern, "", "value");
+            assert_eq!(args.target, H5Path::from("."));
+        }
+
+        #[test]
+        fn parse_pattern_attribute_with_value_with_target() {
+            let args = parse_args(&["@2_3=iU", "/entry/path/"]);
+            assert_pattern_attr_key_value(&args.pattern, "2_3", "iU");
+            assert_eq!(args.target, H5Path::from("/entry/path/"));
+        }
+    }
+
+    mod printer {
+        use super::super::*;
+        use crossterm::{
+            execute,
+            style::{Attribute, Print, ResetColor, SetAttribute},
+        };
+
+        fn write_match(
+            target: &H5Path,
+            path: &H5Path,
+            pattern: &str,
+            location_type: hdf5::LocationType,
+        ) -> String {
+            let mat = Regex::new(pattern).unwrap().find(path.as_raw()).unwrap();
+
+            let printer = Printer::new(true);
+            let mut buffer: Vec<u8> = Vec::new();
+            write_matched_path(
+                &mut buffer,
+                target,
+                path,
+                Some(mat.range()),
+                location_type,
+                &printer,
+            )
+            .unwrap();
+            String::from_utf8(buffer).unwrap()
+        }
+
+        #[test]
+        fn write_match_dataset_in_cwd() {
+            let target = H5Path::from(".");
+            let path = H5Path::from("foo");
+            let res = write_match(&target, &path, "oo", hdf5::LocationType::Dataset);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            execute!(
+                buffer,
+                Print("f"),
+                SetAttribute(Attribute::Underlined),
+                Print("oo"),
+                ResetColor,
+                SetAttribute(Attribute::Reset),
+                Print("\n")
+            )
+            .unwrap();
+            let expected = String::from_utf8(buffer).unwrap();
+
+            assert_eq!(res, expected);
+        }
+
+        #[test]
+        fn write_match_group_in_cwd() {
+            let target = H5Path::from(".");
+            let path = H5Path::from("group");
+            let res = write_match(&target, &path, "rou", hdf5::LocationType::Group);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            execute!(
+                buffer,
+                &Printer::new(true).style().group,
+                Print("g"),
+                SetAttribute(Attribute::Underlined),
+                Print("rou"),
+                ResetColor,
+                &Printer::new(true).style().group,
+                Print("p"),
+                // Some extra reset in case the dataset style is different from the default:
+                ResetColor,
+                Print("/\n")
+            )
+            .unwrap();
+            let expected = String::from_utf8(buffer).unwrap();
+
+            assert_eq!(res, expected);
+        }
+
+        #[test]
+        fn write_match_dataset_in_subdir_full_match() {
+            let target = H5Path::from("folder");
+            let path = H5Path::from("foo");
+            let res = write_match(&target, &path, "foo", hdf5::LocationType::Dataset);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            execute!(
+                buffer,
+                &Printer::new(true).style().group,
+                Print("folder/"),
+                ResetColor,
+                SetAttribute(Attribute::Underlined),
+                Print("foo"),
+                ResetColor,
+                SetAttribute(Attribute::Reset),
+                Print("\n")
+            )
+            .unwrap();
+            let expected = String::from_utf8(buffer).unwrap();
+
+            assert_eq!(res, expected);
+        }
+
+        #[test]
+        fn write_match_dataset_in_subdir_match_end() {
+            let target = H5Path::from("folder");
+            let path = H5Path::from("foo");
+            let res = write_match(&target, &path, "oo", hdf5::LocationType::Dataset);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            execute!(
+                buffer,
+                &Printer::new(true).style().group,
+                Print("folder/"),
+                ResetColor,
+                Print("f"),
+                SetAttribute(Attribute::Underlined),
+                Print("oo"),
+                ResetColor,
+                SetAttribute(Attribute::Reset),
+                Print("\n")
+            )
+            .unwrap();
+            let expected = String::from_utf8(buffer).unwrap();
+
+            assert_eq!(res, expected);
+        }
+
+        #[test]
+        fn write_match_group_in_subdir() {
+            let target = H5Path::from("folder");
+            let path = H5Path::from("group");
+            let res = write_match(&target, &path, "rou", hdf5::LocationType::Group);
+
+            let mut buffer: Vec<u8> = Vec::new();
+            execute!(
+                buffer,
+                &Printer::new(true).style().group,
+                Print("folder/"),
+                &Printer::new(true).style().group,
+                Print("g"),
+                SetAttribute(Attribute::Underlined),
+                Print("rou"),
+                ResetColor,
+                &Printer::new(true).style().group,
+                Print("p"),
+                ResetColor,
+                Print("/\n")
+            )
+            .unwrap();
+            let expected = String::from_utf8(buffer).unwrap();
+
+            assert_eq!(res, expected);
+        }
+    }
+
+    mod values {
+        use super::super::*;
+
+        #[test]
+        fn unreadable_dataset_does_not_stop_search() {
+            let path =
+                std::env::temp_dir().join(format!("h5sh-find-values-{}.h5", std::process::id()));
+            let file = hdf5::File::create(&path).unwrap();
+            // The external file does not exist, so the data cannot be read.
+            file.new_dataset::<f64>()
+                .shape([4])
+                .external("h5sh-missing-external.bin", 0, 32)
+                .create("broken")
+                .unwrap();
+            file.new_dataset_builder()
+                .with_data(&[1.0, f64::NAN])
+                .create("good")
+                .unwrap();
+            let root = H5Group::from_underlying_with_path(file.group("/").unwrap(), "/".into());
+
+            let printer = Printer::capturing();
+            let mut search = Search {
+                pattern: None,
+                filter: None,
+                min_depth: 1,
+                max_depth: None,
+                follow_links: false,
+                value: Some(ValueQuery::parse_contains("nan").unwrap()),
+                output: Output::Plain,
+                found: Vec::new(),
+                count: 0,
+                failed: Vec::new(),
+                shown: HashMap::new(),
+                printer: &printer,
+            };
+            let mut output = printer.progress("Searching…");
+            let result = search.walk(&mut output, &root, &H5Path::from("."));
+            output.finish();
+            let failed = search.failed;
+            drop(file);
+            let _ = std::fs::remove_file(path);
+
+            assert!(result.is_ok());
+            assert_eq!(failed.len(), 1);
+            assert!(failed[0].contains("broken"));
+            assert_eq!(printer.into_captured(), "good\n");
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Search the values of datasets and attributes.
+//!
+//! Datasets are read in blocks along their first axis so that large datasets
+//! do not have to fit into memory.
+
+use hdf5::{
+    H5Type, Hyperslab, SliceOrIndex,
+    types::{FixedAscii, FixedUnicode, TypeDescriptor, VarLenAscii, VarLenUnicode},
+};
+use ndarray::{Array, Dimension, IxDyn};
+use regex::Regex;
+
+use super::filter::{Operator, split_operator};
+use crate::h5;
+use crate::interrupt;
+
+/// The number of elements to read at once.
+const BLOCK_SIZE: usize = 1 << 20;
+/// The longest fixed-length strings that can be searched.
+const MAX_STRING_LENGTH: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub(super) enum ValueQuery {
+    /// Numbers that compare to a value, e.g., 'x > 1e6'.
+    Compare { operator: Operator, value: f64 },
+    /// Numbers that are NaN or infinite.
+    NonFinite(NonFinite),
+    /// Strings that match a regex.
+    Contains(Regex),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum NonFinite {
+    Nan,
+    Inf,
+    PositiveInf,
+    NegativeInf,
+}
+
+impl ValueQuery {
+    /// Parse a comparison like 'x > 1e6', 'x != 0', or '<= -1'.
+    pub(super) fn parse_comparison(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let s = s.strip_prefix('x').unwrap_or(s);
+        let (operator, value) = split_operator(s);
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid number: '{}'", value.trim()))?;
+        Ok(Self::Compare { operator, value })
+    }
+
+    /// Parse a search for 'NaN', 'inf', '+inf', '-inf', or a regex for strings.
+    ///
+    /// The keywords only match numbers, not strings.
+    pub(super) fn parse_contains(s: &str) -> Result<Self, String> {
+        let non_finite = match s.trim().to_lowercase().as_str() {
+            "nan" => NonFinite::Nan,
+            "inf" | "infinity" => NonFinite::Inf,
+            "+inf" | "+infinity" => NonFinite::PositiveInf,
+            "-inf" | "-infinity" => NonFinite::NegativeInf,
+            _ => {
+                let pattern = Regex::new(s).map_err(|err| err.to_string())?;
+                return Ok(Self::Contains(pattern));
+            }
+        };
+        Ok(Self::NonFinite(non_finite))
+    }
+
+    fn matches_number(&self, x: f64) -> bool {
+        match self {
+            ValueQuery::Compare { operator, value } => operator.apply(x, *value),
+            ValueQuery::NonFinite(non_finite) => match non_finite {
+                NonFinite::Nan => x.is_nan(),
+                NonFinite::Inf => x.is_infinite(),
+                NonFinite::PositiveInf => x == f64::INFINITY,
+                NonFinite::NegativeInf => x == f64::NEG_INFINITY,
+            },
+            ValueQuery::Contains(_) => false,
+        }
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        match self {
+            ValueQuery::Compare { .. } | ValueQuery::NonFinite(_) => false,
+            ValueQuery::Contains(pattern) => pattern.is_match(text),
+        }
+    }
+
+    /// Return the index of the first element of `dataset` that matches.
+    pub(super) fn find_in_dataset(
+        &self,
+        dataset: &hdf5::Dataset,
+    ) -> h5::Result<Option<Vec<usize>>> {
+        self.find_in(dataset, true, dataset.chunk())
+    }
+
+    /// Return the index of the first element of `attribute` that matches.
+    pub(super) fn find_in_attribute(
+        &self,
+        attribute: &hdf5::Attribute,
+    ) -> h5::Result<Option<Vec<usize>>> {
+        // Attributes cannot be sliced but are small.
+        self.find_in(attribute, false, None)
+    }
+
+    /// Containers with a dtype that cannot be searched never match.
+    fn find_in(
+        &self,
+        container: &hdf5::Container,
+        sliceable: bool,
+        chunk: Option<Vec<usize>>,
+    ) -> h5::Result<Option<Vec<usize>>> {
+        let Ok(descriptor) = container.dtype()?.to_descriptor() else {
+            return Ok(None);
+        };
+        let reader = Reader {
+            container,
+            sliceable,
+            chunk,
+        };
+        match descriptor {
+            TypeDescriptor::Float(_) | TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) => {
+                reader.find::<f64>(|x| self.matches_number(*x))
+            }
+            TypeDescriptor::Boolean => {
+                reader.find::<bool>(|x| self.matches_number(*x as u8 as f64))
+            }
+            TypeDescriptor::VarLenUnicode => reader.find::<VarLenUnicode>(|x| self.matches_text(x)),
+            TypeDescriptor::VarLenAscii => reader.find::<VarLenAscii>(|x| self.matches_text(x)),
+            TypeDescriptor::FixedUnicode(n) if n <= MAX_STRING_LENGTH => {
+                reader.find::<FixedUnicode<MAX_STRING_LENGTH>>(|x| self.matches_text(x))
+            }
+            TypeDescriptor::FixedAscii(n) if n <= MAX_STRING_LENGTH => {
+                reader.find::<FixedAscii<MAX_STRING_LENGTH>>(|x| self.matches_text(x))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+struct Reader<'c> {
+    container: &'c hdf5::Container,
+    sliceable: bool,
+    chunk: Option<Vec<usize>>,
+}
+
+impl Reader<'_> {
+    fn find<T: H5Type>(&self, matches: impl Fn(&T) -> bool) -> h5::Result<Option<Vec<usize>>> {
+        let shape = self.container.shape();
+        if !self.sliceable || shape.is_empty() {
+            let data = self.container.read::<T, IxDyn>()?;
+            return Ok(first_match(&data, &matches));
+        }
+
+        let n_rows = shape[0];
+        let block_rows = self.block_rows(&shape);
+        for start in (0..n_rows).step_by(block_rows) {
+            // The caller reports the interrupt.
+            if interrupt::is_interrupted() {
+                return Ok(None);
+            }
+            let end = (start + block_rows).min(n_rows);
+            let selection: Vec<SliceOrIndex> = std::iter::once((start..end).into())
+                .chain(shape[1..].iter().map(|_| (..).into()))
+                .collect();
+            let data = self
+                .container
+                .read_slice::<T, _, IxDyn>(Hyperslab::from(selection))?;
+            if let Some(mut index) = first_match(&data, &matches) {
+                index[0] += start;
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return the number of rows along the first axis to read at once.
+    ///
+    /// Blocks are aligned with chunks to avoid decompressing chunks multiple times.
+    fn block_rows(&self, shape: &[usize]) -> usize {
+        let row_size = shape[1..].iter().product::<usize>().max(1);
+        let rows = (BLOCK_SIZE / row_size).max(1);
+        match self.chunk.as_deref() {
+            Some([chunk_rows, ..]) if *chunk_rows > 0 => rows.div_ceil(*chunk_rows) * chunk_rows,
+            _ => rows,
+        }
+    }
+}
+
+fn first_match<T>(data: &Array<T, IxDyn>, matches: impl Fn(&T) -> bool) -> Option<Vec<usize>> {
+    data.indexed_iter()
+        .find(|(_, x)| matches(x))
+        .map(|(index, _)| index.slice().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_comparison() {
+        let query = ValueQuery::parse_comparison("x > 1e6").unwrap();
+        assert!(query.matches_number(2e6));
+        assert!(!query.matches_number(1e6));
+        let query = ValueQuery::parse_comparison("!= 0").unwrap();
+        assert!(query.matches_number(-1.0));
+        assert!(!query.matches_number(0.0));
+        assert!(!query.matches_text("1"));
+        assert!(ValueQuery::parse_comparison("x > y").is_err());
+    }
+
+    #[test]
+    fn contains_non_finite() {
+        let query = ValueQuery::parse_contains("NaN").unwrap();
+        assert!(query.matches_number(f64::NAN));
+        assert!(!query.matches_number(f64::INFINITY));
+        assert!(!query.matches_text("value is NaN"));
+        let query = ValueQuery::parse_contains("-inf").unwrap();
+        assert!(query.matches_number(f64::NEG_INFINITY));
+        assert!(!query.matches_number(f64::INFINITY));
+        let query = ValueQuery::parse_contains("inf").unwrap();
+        assert!(query.matches_number(f64::NEG_INFINITY));
+        assert!(query.matches_number(f64::INFINITY));
+        assert!(!query.matches_text("inf"));
+    }
+
+    #[test]
+    fn contains_regex() {
+        let query = ValueQuery::parse_contains("^NX(detector|monitor)$").unwrap();
+        assert!(query.matches_text("NXmonitor"));
+        assert!(!query.matches_text("NXsample"));
+        assert!(!query.matches_number(0.0));
+    }
+
+    #[test]
+    fn first_match_returns_multi_dimensional_index() {
+        let data = Array::from_shape_vec(IxDyn(&[2, 3]), vec![0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(first_match(&data, |x| *x > 3), Some(vec![1, 1]));
+        assert_eq!(first_match(&data, |x| *x > 5), None);
+    }
+}
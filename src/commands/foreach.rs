@@ -0,0 +1,144 @@
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::{H5File, glob};
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Foreach;
+
+impl Command for Foreach {
+    fn run(&self, args: ArgMatches, _shell: &Shell, _file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        if args.command.is_empty() {
+            return Err(CommandError::Error(
+                "Missing command, pass it after '--'".to_string(),
+            ));
+        }
+        Ok(CommandOutcome::Foreach {
+            inputs: build_inputs(&args.paths, &join_words(&args.command)),
+            stop_on_error: args.stop_on_error,
+        })
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name("foreach"),
+    verbatim_doc_comment,
+    after_help = "Examples:
+
+Inspect the data of all detectors:
+  foreach $(find --plain ^detector_) -- inspect {}/data
+
+List several groups:
+  foreach /entry/sample /entry/instrument -- ls -l"
+)]
+/// Run a command for each path.
+///
+/// Occurrences of '{}' in the command are replaced by the path.
+/// If there are none, the path is appended to the command.
+/// Special characters in paths are escaped, so they are used literally.
+struct Arguments {
+    /// Paths to run the command for.
+    #[arg(required = true, value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+
+    /// Command to run, separated from the paths by '--'.
+    #[arg(last = true)]
+    command: Vec<String>,
+
+    /// Stop at the first command that fails.
+    #[arg(short = 'e', long)]
+    stop_on_error: bool,
+}
+
+pub(super) fn build_inputs(paths: &[String], command: &str) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            let path = glob::escape(path);
+            if command.contains("{}") {
+                command.replace("{}", &path)
+            } else {
+                format!("{command} {path}")
+            }
+        })
+        .collect()
+}
+
+/// Join the words of a command so that they are split into the same words again.
+///
+/// Only characters that separate or quote words are escaped,
+/// so that `@` and glob patterns keep their meaning.
+fn join_words(words: &[String]) -> String {
+    let mut joined = String::new();
+    for word in words {
+        if !joined.is_empty() {
+            joined.push(' ');
+        }
+        for c in word.chars() {
+            if c.is_whitespace() || matches!(c, '\'' | '"' | '\\' | '$') {
+                joined.push('\\');
+            }
+            joined.push(c);
+        }
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse_args(args: &[&str]) -> Arguments {
+        Arguments::try_parse_from(std::iter::once("foreach").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn parse_paths_and_command() {
+        let args = parse_args(&["a", "b/c", "--", "ls", "-l", "{}"]);
+        assert_eq!(args.paths, vec!["a", "b/c"]);
+        assert_eq!(args.command, vec!["ls", "-l", "{}"]);
+        assert!(!args.stop_on_error);
+    }
+
+    #[test]
+    fn parse_stop_on_error() {
+        let args = parse_args(&["-e", "a", "--", "ls"]);
+        assert!(args.stop_on_error);
+    }
+
+    #[test]
+    fn build_inputs_with_placeholder() {
+        let inputs = build_inputs(&["/a".to_string(), "b".to_string()], "inspect {}/data");
+        assert_eq!(inputs, vec!["inspect /a/data", "inspect b/data"]);
+    }
+
+    #[test]
+    fn build_inputs_escapes_paths() {
+        let inputs = build_inputs(&["a b*".to_string()], "ls");
+        assert_eq!(inputs, vec![r"ls a\ b\*"]);
+    }
+
+    #[test]
+    fn build_inputs_without_placeholder() {
+        let inputs = build_inputs(&["/a".to_string()], "ls -l");
+        assert_eq!(inputs, vec!["ls -l /a"]);
+    }
+
+    #[test]
+    fn join_words_keeps_words_with_spaces() {
+        let command = join_words(&["a".to_string(), "{}@my attr".to_string()]);
+        assert_eq!(command, r"a {}@my\ attr");
+        let inputs = build_inputs(&["/x".to_string()], &command);
+        assert_eq!(inputs, vec![r"a /x@my\ attr"]);
+    }
+}
@@ -5,7 +5,7 @@ use crate::shell::Shell;
 use crate::output::Style;
 use clap::{ArgMatches, CommandFactory, Parser};
 use crossterm::{QueueableCommand, style::Print};
-use std::io::{Write, stdout};
+use std::io::Write;
 
 #[derive(Clone, Copy, Default)]
 pub struct Help;
@@ -27,7 +27,7 @@ impl Command for Help {
 struct Arguments {}
 
 fn print_help(shell: &Shell) -> std::io::Result<()> {
-    let mut stdout = stdout();
+    let mut stdout = shell.printer().stdout();
 
     stdout.queue(Print("Commands:\n"))?;
     let base_commands = collect_base_commands(shell);
@@ -0,0 +1,109 @@
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use std::path::Path;
+
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::H5File;
+use crate::history::Entry;
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct History;
+
+impl Command for History {
+    fn run(&self, args: ArgMatches, shell: &Shell, _file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        let history = shell.history();
+        let mut entries: Vec<_> = history
+            .numbered()
+            .filter(|(_, entry)| args.all || entry.is_of_file(history.file()))
+            .filter(|(_, entry)| {
+                args.pattern
+                    .as_ref()
+                    .is_none_or(|pattern| entry.command.contains(pattern.as_str()))
+            })
+            .collect();
+        if let Some(limit) = args.limit {
+            entries.drain(..entries.len().saturating_sub(limit));
+        }
+        for (number, entry) in entries {
+            shell
+                .printer()
+                .println(format_entry(number, entry, args.all));
+        }
+        Ok(CommandOutcome::KeepRunning)
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name("history"),
+    verbatim_doc_comment,
+    after_help = "Examples:
+
+Search for commands that inspected a detector:
+  history inspect
+  history --all detector
+
+Run commands again:
+  !!      the previous command
+  !42     the command with number 42
+  !-2     the command before the previous one
+  !cat    the most recent command that starts with 'cat'"
+)]
+/// Show the command history of the current file.
+///
+/// Commands are numbered across all files.
+/// Run a command again with '!' followed by its number.
+struct Arguments {
+    /// Only show commands that contain this text.
+    pattern: Option<String>,
+
+    /// Show the commands of all files.
+    #[arg(short, long)]
+    all: bool,
+
+    /// Show only the last N commands.
+    #[arg(short = 'n', long, value_name = "N")]
+    limit: Option<usize>,
+}
+
+fn format_entry(number: usize, entry: &Entry, show_file: bool) -> String {
+    if show_file {
+        let file_name = Path::new(&entry.file)
+            .file_name()
+            .map_or(entry.file.as_str(), |name| name.to_str().unwrap_or(""));
+        format!("{number:>5}  {file_name}  {}", entry.command)
+    } else {
+        format!("{number:>5}  {}", entry.command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_entry_of_current_file() {
+        let entry = Entry {
+            file: "/data/scan.h5".to_string(),
+            command: "ls -l".to_string(),
+        };
+        assert_eq!(format_entry(42, &entry, false), "   42  ls -l");
+    }
+
+    #[test]
+    fn format_entry_with_file() {
+        let entry = Entry {
+            file: "/data/scan.h5".to_string(),
+            command: "ls -l".to_string(),
+        };
+        assert_eq!(format_entry(7, &entry, true), "    7  scan.h5  ls -l");
+    }
+}
@@ -0,0 +1,149 @@
+use bumpalo::{Bump, collections::String as BumpString, collections::Vec as BumpVec};
+use clap::{ArgMatches, CommandFactory, Parser};
+use crossterm::{ExecutableCommand, style::Print};
+
+use super::inspect::{write_item, write_title};
+use crate::cmd::{CmdResult, Command, CommandOutcome};
+use crate::h5::{FileInfo, FileStatus, H5File, OpenObjects};
+use crate::output::Printer;
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Info;
+
+impl Command for Info {
+    fn run(&self, _args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let info = file.info()?;
+        let printer = shell.printer();
+        let bump = Bump::new();
+        let buffer = format_info(&info, printer, &bump)?;
+        printer.println(BumpString::from_utf8_lossy_in(&buffer, &bump));
+        Ok(CommandOutcome::KeepRunning)
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Print file-level properties.
+#[derive(Parser, Debug)]
+#[command(name("info"))]
+struct Arguments {}
+
+fn format_info<'alloc>(
+    info: &FileInfo,
+    printer: &Printer,
+    bump: &'alloc Bump,
+) -> std::io::Result<BumpVec<'alloc, u8>> {
+    let size = |size| {
+        printer
+            .format_human_size_in(size, false, bump)
+            .trim_end()
+            .to_string()
+    };
+
+    let mut buffer = BumpVec::<u8>::new_in(bump);
+    write_title(
+        &mut buffer,
+        "File",
+        &printer.style().emphasis,
+        None,
+        printer,
+    )?
+    .execute(Print('\n'))?;
+
+    write_item(&mut buffer, "Path", info.path.display(), printer)?.execute(Print('\n'))?;
+    match (info.size, info.disk_size) {
+        (Some(hdf5_size), Some(disk_size)) => {
+            write_item(&mut buffer, "Size", size(hdf5_size), printer)?.execute(Print("  "))?;
+            write_item(&mut buffer, "On disk", size(disk_size), printer)?;
+        }
+        (Some(hdf5_size), None) => {
+            write_item(&mut buffer, "Size", size(hdf5_size), printer)?;
+        }
+        (None, Some(disk_size)) => {
+            write_item(&mut buffer, "On disk", size(disk_size), printer)?;
+        }
+        (None, None) => {
+            write_item(&mut buffer, "Size", "unknown", printer)?;
+        }
+    }
+    buffer.execute(Print('\n'))?;
+
+    write_item(&mut buffer, "Driver", info.driver, printer)?.execute(Print("  "))?;
+    write_item(&mut buffer, "SWMR", info.swmr, printer)?.execute(Print('\n'))?;
+
+    write_item(
+        &mut buffer,
+        "Superblock",
+        format_args!(
+            "version {}, {}, extension {}",
+            info.superblock_version,
+            size(info.superblock_size),
+            size(info.superblock_ext_size)
+        ),
+        printer,
+    )?
+    .execute(Print('\n'))?;
+    write_item(&mut buffer, "Userblock", size(info.userblock_size), printer)?
+        .execute(Print('\n'))?;
+    write_item(
+        &mut buffer,
+        "Library versions",
+        format_args!("{} to {}", info.libver_low, info.libver_high),
+        printer,
+    )?
+    .execute(Print('\n'))?;
+
+    write_item(&mut buffer, "Free space", size(info.free_space), printer)?.execute(Print("  "))?;
+    write_item(
+        &mut buffer,
+        "Free-space manager",
+        format_args!(
+            "version {}, {} metadata",
+            info.free_space_version,
+            size(info.free_space_meta_size)
+        ),
+        printer,
+    )?
+    .execute(Print('\n'))?;
+
+    write_item(
+        &mut buffer,
+        "Open objects",
+        format_open_objects(&info.open_objects),
+        printer,
+    )?
+    .execute(Print('\n'))?;
+    write_item(
+        &mut buffer,
+        "Closed cleanly",
+        match info.status {
+            FileStatus::Closed => "yes",
+            FileStatus::OpenForWriting => "no, open for writing",
+            FileStatus::OpenForSwmrWriting => "no, open for SWMR writing",
+            FileStatus::NotRecorded => "unknown, only recorded by superblock version 2 and later",
+            FileStatus::NotAvailable => "unknown, not available for this driver",
+        },
+        printer,
+    )?;
+    Ok(buffer)
+}
+
+fn format_open_objects(objects: &OpenObjects) -> String {
+    [
+        (objects.files, "file", "files"),
+        (objects.groups, "group", "groups"),
+        (objects.datasets, "dataset", "datasets"),
+        (objects.datatypes, "datatype", "datatypes"),
+        (objects.attributes, "attribute", "attributes"),
+    ]
+    .iter()
+    .map(|&(n, singular, plural)| match n {
+        1 => format!("1 {singular}"),
+        n => format!("{n} {plural}"),
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
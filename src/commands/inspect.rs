@@ -1,5 +1,6 @@
 use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
 use crate::h5::{H5Attribute, H5Dataset, H5File, H5Group, H5Object, ObjectPath};
+use crate::interrupt;
 use crate::output::{
     Printer,
     style::{ATTRIBUTE_CHARACTER, DATASET_CHARACTER, GROUP_CHARACTER},
@@ -22,6 +23,9 @@ use ndarray::IxDyn;
 use std::fmt::Display;
 use std::ops::Deref;
 
+/// Number of elements to accumulate between checks for Ctrl-C.
+const INTERRUPT_CHECK_INTERVAL: usize = 1 << 16;
+
 #[derive(Clone, Copy, Default)]
 pub struct Inspect;
 
@@ -30,18 +34,21 @@ impl Command for Inspect {
         let Ok(args) = Arguments::from_arg_matches(&args) else {
             return Err(CommandError::Critical("Failed to extract args".to_string()));
         };
-        let full_path = ObjectPath {
-            location_path: shell.resolve_path(&args.path.location_path),
-            attr_name: args.path.attr_name,
-        };
-        match file.load_object(&full_path) {
-            Ok(object) => match object {
-                H5Object::Group(group) => inspect_group(group, shell.printer()),
-                H5Object::Dataset(dataset) => inspect_dataset(dataset, shell.printer()),
-                H5Object::Attribute(attribute) => inspect_attr(attribute, shell.printer()),
-            },
-            Err(err) => Err(err.into()),
+        for path in args.paths {
+            if interrupt::is_interrupted() {
+                return Err(CommandError::Interrupted);
+            }
+            let full_path = ObjectPath {
+                location_path: shell.resolve_path(&path.location_path),
+                attr_name: path.attr_name,
+            };
+            match file.load_object(&full_path)? {
+                H5Object::Group(group) => inspect_group(group, shell.printer())?,
+                H5Object::Dataset(dataset) => inspect_dataset(dataset, shell.printer())?,
+                H5Object::Attribute(attribute) => inspect_attr(attribute, shell.printer())?,
+            };
         }
+        Ok(CommandOutcome::KeepRunning)
     }
 
     fn arg_parser(&self) -> clap::Command {
@@ -53,8 +60,9 @@ impl Command for Inspect {
 #[derive(Parser, Debug)]
 #[command(name("inspect"))]
 struct Arguments {
-    /// Path of a dataset, group, or attribute.
-    path: ObjectPath,
+    /// Paths of datasets, groups, or attributes.
+    #[arg(required = true, value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<ObjectPath>,
 }
 
 fn inspect_group(group: H5Group, printer: &Printer) -> CmdResult {
@@ -70,7 +78,7 @@ fn inspect_group(group: H5Group, printer: &Printer) -> CmdResult {
 
     let (n_group, n_ds, n_dtype, n_map) = locations.iter().fold(
         (0, 0, 0, 0),
-        |(n_group, n_ds, n_dtype, n_map), (_, loc)| match loc.loc_type {
+        |(n_group, n_ds, n_dtype, n_map), (_, loc, _)| match loc.loc_type {
             hdf5::LocationType::Group => (n_group + 1, n_ds, n_dtype, n_map),
             hdf5::LocationType::Dataset => (n_group, n_ds + 1, n_dtype, n_map),
             hdf5::LocationType::NamedDatatype => (n_group, n_ds, n_dtype + 1, n_map),
@@ -271,7 +279,7 @@ fn any_unsigned_int<'alloc>(
     }
 }
 
-trait IsNonFinite {
+pub(super) trait IsNonFinite {
     fn can_be_non_finite() -> bool {
         false
     }
@@ -322,7 +330,7 @@ impl IsNonFinite for u32 {}
 impl IsNonFinite for u16 {}
 impl IsNonFinite for u8 {}
 
-trait Number:
+pub(super) trait Number:
     std::ops::Add<Output = Self>
     + std::ops::Div<Output = Self>
     + std::ops::Sub<Output = Self>
@@ -350,7 +358,8 @@ macro_rules! impl_number {
 
 impl_number!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
 
-struct NumberAccumulator<T> {
+/// Statistics of numbers that can be updated as more numbers come in.
+pub(super) struct NumberAccumulator<T> {
     min: T,
     max: T,
     // Accumulate in f64 for the best precision.
@@ -363,7 +372,7 @@ struct NumberAccumulator<T> {
 }
 
 impl<T: Number> NumberAccumulator<T> {
-    fn accumulate(acc: Option<Self>, x: &T) -> Option<Self> {
+    pub(super) fn accumulate(acc: Option<Self>, x: &T) -> Option<Self> {
         match acc {
             Some(acc) => {
                 let new_n = acc.n_normal + if Self::is_normal(*x) { 1 } else { 0 };
@@ -441,9 +450,27 @@ where
     T: Number + H5Type + Display + std::fmt::Debug,
 {
     let content = container.read::<T, IxDyn>()?;
+    let mut acc = None;
+    for (index, x) in content.iter().enumerate() {
+        if index % INTERRUPT_CHECK_INTERVAL == 0 && interrupt::is_interrupted() {
+            return Err(CommandError::Interrupted);
+        }
+        acc = NumberAccumulator::accumulate(acc, x);
+    }
+    write_number_stats(content.shape(), acc.as_ref(), printer, bump)
+}
 
-    let acc = content.iter().fold(None, NumberAccumulator::accumulate);
-    let volume = content.len();
+/// Write the statistics of a dataset or attribute with the given shape.
+pub(super) fn write_number_stats<'alloc, T>(
+    shape: &[usize],
+    acc: Option<&NumberAccumulator<T>>,
+    printer: &Printer,
+    bump: &'alloc Bump,
+) -> Result<BumpVec<'alloc, u8>, CommandError>
+where
+    T: Number + H5Type + Display + std::fmt::Debug,
+{
+    let volume = shape.iter().product::<usize>();
 
     let mut buffer = BumpVec::<u8>::new_in(bump);
 
@@ -455,7 +482,7 @@ where
     )?
     .execute(Print('\n'))?;
 
-    write_item_debug(&mut buffer, "Shape", content.shape(), printer, bump)?.execute(Print("  "))?;
+    write_item_debug(&mut buffer, "Shape", shape, printer, bump)?.execute(Print("  "))?;
     write_item(&mut buffer, "Volume", volume, printer)?.execute(Print("  "))?;
     write_item(
         &mut buffer,
@@ -528,7 +555,7 @@ fn boolean<'alloc>(
     Ok(buffer)
 }
 
-fn write_item<'e, E: ExecutableCommand, T: Display>(
+pub(super) fn write_item<'e, E: ExecutableCommand, T: Display>(
     e: &'e mut E,
     label: &str,
     value: T,
@@ -547,7 +574,7 @@ fn write_item_debug<'e, E: ExecutableCommand, T: std::fmt::Debug>(
     write_label(e, label, printer)?.execute(Print(format!(in bump, "{:?}", value)))
 }
 
-fn write_label<'e, E: ExecutableCommand>(
+pub(super) fn write_label<'e, E: ExecutableCommand>(
     e: &'e mut E,
     label: &str,
     printer: &Printer,
@@ -558,7 +585,7 @@ fn write_label<'e, E: ExecutableCommand>(
         .execute(Print(": "))
 }
 
-fn write_title<'e, E: ExecutableCommand>(
+pub(super) fn write_title<'e, E: ExecutableCommand>(
     e: &'e mut E,
     title: &str,
     style: &crate::output::style::Item,
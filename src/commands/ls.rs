@@ -1,9 +1,9 @@
 use bumpalo::Bump;
 use clap::{ArgGroup, ArgMatches, CommandFactory, FromArgMatches, Parser};
-use std::io::stdout;
 
 use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
 use crate::h5::{H5File, H5Object, H5Path};
+use crate::interrupt;
 use crate::output::Printer;
 use crate::shell::Shell;
 
@@ -16,17 +16,29 @@ impl Command for Ls {
             return Err(CommandError::Critical("Failed to extract args".to_string()));
         };
         let options = Options::from_args(&args);
-        let target = shell.resolve_path(&args.path);
-
-        match file.load(&target)? {
-            H5Object::Group(group) => {
-                print_objects(group.load_children()?.into_iter(), shell.printer(), options);
+        let printer = shell.printer();
+
+        for (index, path) in args.paths.iter().enumerate() {
+            if args.paths.len() > 1 {
+                if index > 0 {
+                    printer.println("");
+                }
+                printer.println(format!("{path}:"));
             }
-            dataset @ H5Object::Dataset(_) => {
-                print_objects(std::iter::once(dataset), shell.printer(), options);
+            match file.load(&shell.resolve_path(path))? {
+                H5Object::Group(group) => {
+                    print_objects(file.load_children(&group)?.into_iter(), printer, &options);
+                }
+                dataset @ H5Object::Dataset(_) => {
+                    print_objects(std::iter::once(dataset), printer, &options);
+                }
+                H5Object::Attribute(_) => {
+                    return Err(CommandError::Error("Is an attribute".to_string()));
+                }
             }
-            H5Object::Attribute(_) => {
-                return Err(CommandError::Error("Is an attribute".to_string()));
+            // The table stops loading content after an interrupt.
+            if interrupt::is_interrupted() {
+                return Err(CommandError::Interrupted);
             }
         }
         Ok(CommandOutcome::KeepRunning)
@@ -42,9 +54,9 @@ impl Command for Ls {
 #[command(name("ls"))]
 #[clap(group(ArgGroup::new("sort").args(&["name", "ty"])))]
 struct Arguments {
-    /// List children of this path.
-    #[arg(default_value = ".")]
-    path: H5Path,
+    /// List children of these paths.
+    #[arg(default_value = ".", value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<H5Path>,
 
     /// Show object metadata in a table.
     #[arg(short = 'l', default_value_t = false)]
@@ -69,6 +81,7 @@ struct Options {
     show_content: bool,
 }
 
+#[derive(Clone, Copy)]
 enum SortBy {
     Name,
     Type,
@@ -92,7 +105,7 @@ impl Options {
     }
 }
 
-fn print_objects<It: Iterator<Item = H5Object>>(objects: It, printer: &Printer, options: Options) {
+fn print_objects<It: Iterator<Item = H5Object>>(objects: It, printer: &Printer, options: &Options) {
     let objects_vec: Vec<_> = objects.collect();
     let mut objects = objects_vec
         .iter()
@@ -122,7 +135,7 @@ fn print_object_table(
     show_content: bool,
 ) -> std::io::Result<()> {
     printer
-        .queue_object_table(&mut stdout(), &objects, show_content)
+        .queue_object_table(&mut printer.stdout(), &objects, show_content)
         .map(|_| ())
 }
 
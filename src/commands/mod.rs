@@ -1,19 +1,39 @@
 mod attr;
+mod bookmark;
 mod cat;
 mod cd;
+mod dirs;
 mod exit;
 mod find;
+mod foreach;
 mod help;
+mod history;
+mod info;
 mod inspect;
 mod ls;
+mod popd;
+mod pushd;
 mod pwd;
+mod refresh;
+mod set;
+mod watch;
 
 pub use attr::Attr;
+pub use bookmark::Bookmark;
 pub use cat::Cat;
 pub use cd::Cd;
+pub use dirs::Dirs;
 pub use exit::Exit;
 pub use find::Find;
+pub use foreach::Foreach;
 pub use help::Help;
+pub use history::History;
+pub use info::Info;
 pub use inspect::Inspect;
 pub use ls::Ls;
+pub use popd::Popd;
+pub use pushd::Pushd;
 pub use pwd::Pwd;
+pub use refresh::Refresh;
+pub use set::Set;
+pub use watch::Watch;
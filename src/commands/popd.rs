@@ -0,0 +1,36 @@
+use clap::{ArgMatches, CommandFactory, Parser};
+
+use super::cd::resolve_group;
+use super::dirs::{format_stack, stack_groups};
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::H5File;
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Popd;
+
+impl Command for Popd {
+    fn run(&self, _args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let mut stack = shell.group_stack().to_vec();
+        let Some(top) = stack.pop() else {
+            return Err(CommandError::Error("Group stack is empty".to_string()));
+        };
+        let working_group = resolve_group(shell, file, &top)?;
+        shell
+            .printer()
+            .println(format_stack(stack_groups(&working_group, &stack)));
+        Ok(CommandOutcome::ChangeGroupStack {
+            working_group,
+            stack,
+        })
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Remove the top group from the stack and change into it.
+#[derive(Parser, Debug)]
+#[command(name("popd"))]
+struct Arguments {}
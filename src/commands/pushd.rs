@@ -0,0 +1,59 @@
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use super::cd::resolve_group;
+use super::dirs::{format_stack, stack_groups};
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::{H5File, H5Path};
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Pushd;
+
+impl Command for Pushd {
+    fn run(&self, args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        let mut stack = shell.group_stack().to_vec();
+        let working_group = match args.path {
+            Some(path) => {
+                let target = resolve_group(shell, file, &path)?;
+                stack.push(shell.get_working_group().clone());
+                target
+            }
+            None => {
+                let Some(top) = stack.pop() else {
+                    return Err(CommandError::Error(
+                        "No other group on the stack".to_string(),
+                    ));
+                };
+                let top = resolve_group(shell, file, &top)?;
+                stack.push(shell.get_working_group().clone());
+                top
+            }
+        };
+        shell
+            .printer()
+            .println(format_stack(stack_groups(&working_group, &stack)));
+        Ok(CommandOutcome::ChangeGroupStack {
+            working_group,
+            stack,
+        })
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Save the working group on the stack and change group.
+///
+/// Without a path, swap the working group with the group on top of the stack.
+/// Use 'popd' to return to the saved group and 'dirs' to show the stack.
+#[derive(Parser, Debug)]
+#[command(name("pushd"), verbatim_doc_comment)]
+struct Arguments {
+    /// Path to change into.
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    path: Option<H5Path>,
+}
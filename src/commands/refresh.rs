@@ -0,0 +1,36 @@
+use clap::{ArgMatches, CommandFactory, Parser};
+
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::{H5File, H5Object, H5Path};
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Refresh;
+
+impl Command for Refresh {
+    fn run(&self, _args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        file.reopen()
+            .map_err(|err| CommandError::Critical(format!("Failed to reopen the file: {err}")))?;
+        match file.load(shell.get_working_group()) {
+            Ok(H5Object::Group(_)) => Ok(CommandOutcome::KeepRunning),
+            _ => {
+                shell.printer().println(format!(
+                    "Group {} no longer exists, changing to /",
+                    shell.get_working_group()
+                ));
+                Ok(CommandOutcome::ChangeWorkingGroup(H5Path::root()))
+            }
+        }
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Re-read the file after it was modified by another program.
+///
+/// h5sh caches the structure of the file, this command discards the cache.
+#[derive(Parser, Debug)]
+#[command(name("refresh"), verbatim_doc_comment)]
+struct Arguments {}
@@ -0,0 +1,74 @@
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::h5::H5File;
+use crate::line_editor::parse::is_variable_char;
+use crate::shell::Shell;
+
+#[derive(Clone, Copy, Default)]
+pub struct Set;
+
+impl Command for Set {
+    fn run(&self, args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        let Some(assignment) = args.assignment else {
+            for (name, value) in shell.variables(file) {
+                shell.printer().println(format!("{name}={value}"));
+            }
+            return Ok(CommandOutcome::KeepRunning);
+        };
+
+        let Some((name, value)) = assignment.split_once('=') else {
+            return Err(CommandError::Error(format!(
+                "Expected an assignment NAME=VALUE, got '{assignment}'"
+            )));
+        };
+        if name.is_empty() || !name.chars().all(is_variable_char) {
+            return Err(CommandError::Error(format!(
+                "Invalid variable name: '{name}'"
+            )));
+        }
+        if Shell::is_builtin_variable(name) {
+            return Err(CommandError::Error(format!(
+                "Cannot set builtin variable: '{name}'"
+            )));
+        }
+
+        let value = std::iter::once(value)
+            .chain(args.rest.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(CommandOutcome::SetVariable {
+            name: name.to_string(),
+            value,
+        })
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Set a shell variable or list all variables.
+#[derive(Parser, Debug)]
+#[command(name("set"))]
+#[command(after_help = "Examples:
+Store a path in a variable and use it later:
+  set det=/entry/instrument/detector_1
+  ls $det
+
+Store the output of a command:
+  set det=$(find --plain ^detector_1$)
+
+Builtin variables are PWD (the working group), FILE (the file name),
+and ? (the exit status of the last command).")]
+struct Arguments {
+    /// Assignment of the form NAME=VALUE. If omitted, list all variables.
+    assignment: Option<String>,
+
+    /// Further words of the value, joined with spaces.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    rest: Vec<String>,
+}
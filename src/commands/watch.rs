@@ -0,0 +1,253 @@
+use bumpalo::Bump;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use hdf5::H5Type;
+use hdf5::types::{FloatSize, IntSize, TypeDescriptor};
+use std::fmt::Display;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use super::inspect::{Number, NumberAccumulator, write_number_stats};
+use crate::cmd::{CmdResult, Command, CommandError, CommandOutcome};
+use crate::data::load_and_format_rows;
+use crate::h5::{H5Dataset, H5File, H5Object, H5Path};
+use crate::interrupt;
+use crate::output::Printer;
+use crate::shell::Shell;
+
+/// Maximum number of rows to read at once.
+const BLOCK_SIZE: usize = 4096;
+/// Maximum time between checks for Ctrl-C while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, Default)]
+pub struct Watch;
+
+impl Command for Watch {
+    fn run(&self, args: ArgMatches, shell: &Shell, file: &H5File) -> CmdResult {
+        let Ok(args) = Arguments::from_arg_matches(&args) else {
+            return Err(CommandError::Critical("Failed to extract args".to_string()));
+        };
+        let full_path = shell.resolve_path(&args.path);
+        let dataset = match file.load(&full_path)? {
+            H5Object::Dataset(dataset) => dataset,
+            H5Object::Group(_) => {
+                return Err(CommandError::Error(format!("Is a group: {full_path}")));
+            }
+            H5Object::Attribute(_) => {
+                return Err(CommandError::Error(format!("Is an attribute: {full_path}")));
+            }
+        };
+        if dataset.ndim() == 0 {
+            return Err(CommandError::Error(format!(
+                "Cannot watch a scalar dataset: {full_path}"
+            )));
+        }
+        let Ok(interval) = Duration::try_from_secs_f64(args.interval) else {
+            return Err(CommandError::Error(format!(
+                "Invalid interval: {}",
+                args.interval
+            )));
+        };
+
+        let printer = shell.printer();
+        if args.stats {
+            watch_stats(&dataset, interval, printer)
+        } else {
+            watch_rows(&dataset, args.lines, interval, printer)
+        }
+    }
+
+    fn arg_parser(&self) -> clap::Command {
+        Arguments::command()
+    }
+}
+
+/// Print new elements of a growing dataset.
+///
+/// Prints the last rows (elements along the first axis) of a dataset and
+/// then the new rows whenever the dataset grows until Ctrl-C is pressed.
+/// Open the file with '--swmr' to watch a dataset that another program is writing.
+#[derive(Parser, Debug)]
+#[command(name("watch"), verbatim_doc_comment)]
+#[command(after_help = "Examples:
+Print new events as they are written:
+  watch entry/events/time
+
+Show statistics of a dataset and update them every 5 seconds:
+  watch --stats -s 5 entry/detector/data")]
+struct Arguments {
+    /// Path of a dataset.
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    path: H5Path,
+
+    /// Number of existing rows to print.
+    #[arg(short = 'n', long, default_value_t = 10)]
+    lines: usize,
+
+    /// Seconds between checks for new rows.
+    #[arg(short = 's', long, default_value_t = 1.0)]
+    interval: f64,
+
+    /// Show statistics like 'inspect' instead of the rows and update them with new rows.
+    #[arg(long)]
+    stats: bool,
+}
+
+fn watch_rows(
+    dataset: &H5Dataset,
+    lines: usize,
+    interval: Duration,
+    printer: &Printer,
+) -> CmdResult {
+    let mut n_shown = n_rows(dataset);
+    print_rows(dataset, n_shown.saturating_sub(lines)..n_shown, printer)?;
+    while wait(interval) {
+        dataset.refresh()?;
+        let n_rows = n_rows(dataset);
+        if n_rows > n_shown {
+            print_rows(dataset, n_shown..n_rows, printer)?;
+        }
+        n_shown = n_rows;
+    }
+    Ok(CommandOutcome::KeepRunning)
+}
+
+fn print_rows(dataset: &H5Dataset, rows: Range<usize>, printer: &Printer) -> CmdResult {
+    for block in blocks(rows) {
+        if interrupt::is_interrupted() {
+            break;
+        }
+        let bump = Bump::new();
+        printer.println(load_and_format_rows(dataset, block, printer, &bump)?);
+    }
+    Ok(CommandOutcome::KeepRunning)
+}
+
+fn watch_stats(dataset: &H5Dataset, interval: Duration, printer: &Printer) -> CmdResult {
+    match dataset.dtype()?.to_descriptor()? {
+        TypeDescriptor::Float(FloatSize::U8) => {
+            watch_number_stats::<f64>(dataset, interval, printer)
+        }
+        // f16 is unstable, so approximate using f32
+        TypeDescriptor::Float(_) => watch_number_stats::<f32>(dataset, interval, printer),
+        TypeDescriptor::Integer(IntSize::U8) => {
+            watch_number_stats::<i64>(dataset, interval, printer)
+        }
+        TypeDescriptor::Integer(IntSize::U4) => {
+            watch_number_stats::<i32>(dataset, interval, printer)
+        }
+        TypeDescriptor::Integer(IntSize::U2) => {
+            watch_number_stats::<i16>(dataset, interval, printer)
+        }
+        TypeDescriptor::Integer(IntSize::U1) => {
+            watch_number_stats::<i8>(dataset, interval, printer)
+        }
+        TypeDescriptor::Unsigned(IntSize::U8) => {
+            watch_number_stats::<u64>(dataset, interval, printer)
+        }
+        TypeDescriptor::Unsigned(IntSize::U4) => {
+            watch_number_stats::<u32>(dataset, interval, printer)
+        }
+        TypeDescriptor::Unsigned(IntSize::U2) => {
+            watch_number_stats::<u16>(dataset, interval, printer)
+        }
+        TypeDescriptor::Unsigned(IntSize::U1) => {
+            watch_number_stats::<u8>(dataset, interval, printer)
+        }
+        descriptor => Err(CommandError::Error(format!(
+            "Statistics are only supported for numbers, got {}",
+            printer.format_dtype(&descriptor, &Bump::new())
+        ))),
+    }
+}
+
+/// Show statistics and update them with only the new rows whenever the dataset grows.
+fn watch_number_stats<T>(dataset: &H5Dataset, interval: Duration, printer: &Printer) -> CmdResult
+where
+    T: Number + H5Type + Display + std::fmt::Debug,
+{
+    let mut acc = None;
+    let mut n_accumulated = 0;
+    let mut first = true;
+    loop {
+        let shape = dataset.shape();
+        let n_rows = shape[0];
+        if n_rows < n_accumulated {
+            // Rows were removed, start over.
+            acc = None;
+            n_accumulated = 0;
+        }
+        if first || n_rows > n_accumulated {
+            for block in blocks(n_accumulated..n_rows) {
+                if interrupt::is_interrupted() {
+                    return Ok(CommandOutcome::KeepRunning);
+                }
+                acc = dataset
+                    .read_rows::<T>(block)?
+                    .iter()
+                    .fold(acc, NumberAccumulator::accumulate);
+            }
+            n_accumulated = n_rows;
+            if !first {
+                printer.println("");
+            }
+            first = false;
+            let bump = Bump::new();
+            let stats = write_number_stats(&shape, acc.as_ref(), printer, &bump)?;
+            printer.println(String::from_utf8_lossy(&stats));
+        }
+        if !wait(interval) {
+            return Ok(CommandOutcome::KeepRunning);
+        }
+        dataset.refresh()?;
+    }
+}
+
+fn n_rows(dataset: &H5Dataset) -> usize {
+    dataset.shape().first().copied().unwrap_or(0)
+}
+
+/// Split rows into blocks that are small enough to read at once.
+fn blocks(rows: Range<usize>) -> impl Iterator<Item = Range<usize>> {
+    rows.clone()
+        .step_by(BLOCK_SIZE)
+        .map(move |start| start..(start + BLOCK_SIZE).min(rows.end))
+}
+
+/// Sleep for the given time.
+///
+/// Returns false if the user pressed Ctrl-C.
+fn wait(interval: Duration) -> bool {
+    let end = Instant::now() + interval;
+    while !interrupt::is_interrupted() {
+        let now = Instant::now();
+        if now >= end {
+            return true;
+        }
+        std::thread::sleep((end - now).min(POLL_INTERVAL));
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_cover_rows() {
+        let split: Vec<_> = blocks(3..(2 * BLOCK_SIZE + 5)).collect();
+        assert_eq!(
+            split,
+            vec![
+                3..(BLOCK_SIZE + 3),
+                (BLOCK_SIZE + 3)..(2 * BLOCK_SIZE + 3),
+                (2 * BLOCK_SIZE + 3)..(2 * BLOCK_SIZE + 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_of_no_rows_are_empty() {
+        assert_eq!(blocks(7..7).count(), 0);
+    }
+}
@@ -0,0 +1,620 @@
+use anyhow::{Context, Result};
+use crossterm::style::{Attribute, Color};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// User configuration.
+///
+/// Loaded from `config.toml` in the h5sh config directory,
+/// e.g., `~/.config/h5sh/config.toml` on Linux.
+/// All fields are optional and fall back to the builtin defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub style: StyleConfig,
+    pub prompt: PromptConfig,
+    pub history: HistoryConfig,
+    pub editor: EditorConfig,
+    /// Flags that are inserted before the user-provided arguments of a command.
+    pub default_flags: HashMap<String, String>,
+    /// Commands that are run when a key is pressed, e.g., `alt-up = "cd .."`.
+    pub keybindings: HashMap<KeyConfig, String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    pub dataset: Option<ItemConfig>,
+    pub group: Option<ItemConfig>,
+    pub attribute: Option<ItemConfig>,
+    pub size: Option<ItemConfig>,
+    pub shape: Option<ItemConfig>,
+    pub dtype: Option<ItemConfig>,
+    pub error: Option<ItemConfig>,
+    pub critical_error: Option<ItemConfig>,
+    pub placeholder: Option<ItemConfig>,
+    pub emphasis: Option<ItemConfig>,
+
+    pub prompt: PromptStyleConfig,
+    pub editor: EditorStyleConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptStyleConfig {
+    pub file_name: Option<ItemConfig>,
+    pub working_group: Option<ItemConfig>,
+    pub nx_class: Option<ItemConfig>,
+    pub mode: Option<ItemConfig>,
+    pub children: Option<ItemConfig>,
+    pub file_size: Option<ItemConfig>,
+    pub status: Option<ItemConfig>,
+    pub duration: Option<ItemConfig>,
+    pub swmr: Option<ItemConfig>,
+    pub char: Option<ItemConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditorStyleConfig {
+    pub plain: Option<ItemConfig>,
+    pub pos_argument: Option<ItemConfig>,
+    pub keyword_argument: Option<ItemConfig>,
+    pub command: Option<ItemConfig>,
+    pub bad_command: Option<ItemConfig>,
+    pub hint: Option<ItemConfig>,
+}
+
+/// Style of a single item, e.g., `{ fg = "dark_cyan", attributes = ["bold"] }`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ItemConfig {
+    pub fg: Option<ConfigColor>,
+    pub bg: Option<ConfigColor>,
+    pub attributes: Vec<ConfigAttribute>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ConfigColor(pub Color);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ConfigAttribute(pub Attribute);
+
+/// A key with modifiers, e.g., `ctrl-l` or `alt-up`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyConfig(pub rustyline::KeyEvent);
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptConfig {
+    /// Format string with `{module}` placeholders, e.g., `"{file}:{group} {char} "`.
+    pub format: PromptFormat,
+    /// The prompt character.
+    pub char: String,
+    /// The prompt for lines that continue a command.
+    pub continuation: String,
+}
+
+/// A parsed prompt format string.
+///
+/// Literal braces are written as `{{` and `}}`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct PromptFormat(pub Vec<PromptSegment>);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PromptSegment {
+    Literal(String),
+    Module(PromptModule),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PromptModule {
+    FileName,
+    WorkingGroup,
+    NxClass,
+    Mode,
+    Children,
+    FileSize,
+    Status,
+    Duration,
+    Swmr,
+    Char,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Location of the history file.
+    pub path: Option<PathBuf>,
+    /// Maximum number of entries.
+    pub size: usize,
+    /// Which duplicate entries are removed.
+    pub dedup: Dedup,
+    /// Recall commands of all files in the editor, e.g., with Ctrl-R,
+    /// instead of only the commands of the current file.
+    pub all_files: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dedup {
+    /// Keep all entries.
+    None,
+    /// Do not add a command if it is the same as the previous one.
+    #[default]
+    Consecutive,
+    /// Remove all earlier occurrences of a command when it is added again.
+    All,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditorConfig {
+    pub edit_mode: EditMode,
+    /// How typed names are matched against paths when completing.
+    pub completion: CompletionMode,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum EditMode {
+    #[default]
+    Emacs,
+    Vi,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionMode {
+    /// Names must start with the typed text.
+    #[default]
+    Prefix,
+    /// Names must contain the typed characters in order, ranked by how well they match.
+    Fuzzy,
+}
+
+impl Config {
+    /// Load the configuration.
+    ///
+    /// If `path` is `None`, load from the default location if the file exists
+    /// and use the default configuration otherwise.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::load_file(path),
+            None => {
+                let path = default_config_path();
+                if path.exists() {
+                    Self::load_file(&path)
+                } else {
+                    Ok(Self::default())
+                }
+            }
+        }
+    }
+
+    fn load_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            format: PromptFormat::try_from(String::from("{file}{group} {char} ")).unwrap(),
+            char: String::from("$"),
+            continuation: String::from("> "),
+        }
+    }
+}
+
+impl TryFrom<String> for PromptFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    if chars.as_str().starts_with('{') {
+                        chars.next();
+                        literal.push('{');
+                        continue;
+                    }
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        return Err(format!("unclosed '{{' in prompt format: '{value}'"));
+                    };
+                    let module = PromptModule::from_name(&rest[..end])?;
+                    if !literal.is_empty() {
+                        segments.push(PromptSegment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(PromptSegment::Module(module));
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => {
+                    if chars.as_str().starts_with('}') {
+                        chars.next();
+                        literal.push('}');
+                    } else {
+                        return Err(format!("unmatched '}}' in prompt format: '{value}'"));
+                    }
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(PromptSegment::Literal(literal));
+        }
+        Ok(PromptFormat(segments))
+    }
+}
+
+impl PromptModule {
+    fn from_name(name: &str) -> std::result::Result<Self, String> {
+        Ok(match name.trim() {
+            "file" => PromptModule::FileName,
+            "group" => PromptModule::WorkingGroup,
+            "nx_class" => PromptModule::NxClass,
+            "mode" => PromptModule::Mode,
+            "children" => PromptModule::Children,
+            "size" => PromptModule::FileSize,
+            "status" => PromptModule::Status,
+            "duration" => PromptModule::Duration,
+            "swmr" => PromptModule::Swmr,
+            "char" => PromptModule::Char,
+            _ => return Err(format!("unknown prompt module: '{name}'")),
+        })
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            size: 1000,
+            dedup: Dedup::default(),
+            all_files: false,
+        }
+    }
+}
+
+impl HistoryConfig {
+    pub fn path(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(|| dirs::cache_dir().unwrap().join("h5sh").join("history.tsv"))
+    }
+
+    /// Location of the history file of versions that did not record files,
+    /// `None` if a path is configured.
+    pub fn legacy_path(&self) -> Option<PathBuf> {
+        match self.path {
+            Some(_) => None,
+            None => Some(self.path().with_file_name("history.txt")),
+        }
+    }
+}
+
+impl From<EditMode> for rustyline::EditMode {
+    fn from(mode: EditMode) -> Self {
+        match mode {
+            EditMode::Emacs => rustyline::EditMode::Emacs,
+            EditMode::Vi => rustyline::EditMode::Vi,
+        }
+    }
+}
+
+impl TryFrom<String> for ConfigColor {
+    type Error = String;
+
+    /// Parse a color name (`dark_cyan`), hex code (`#00aaff`), or ANSI value (`208`).
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let value = value.trim();
+        if let Some(hex) = value.strip_prefix('#') {
+            return parse_hex_color(hex)
+                .map(ConfigColor)
+                .ok_or_else(|| format!("invalid hex color: '{value}'"));
+        }
+        if let Ok(ansi) = value.parse::<u8>() {
+            return Ok(ConfigColor(Color::AnsiValue(ansi)));
+        }
+        Color::try_from(value.to_ascii_lowercase().as_str())
+            .map(ConfigColor)
+            .map_err(|_| format!("unknown color: '{value}'"))
+    }
+}
+
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::Rgb {
+        r: component(0)?,
+        g: component(2)?,
+        b: component(4)?,
+    })
+}
+
+impl TryFrom<String> for ConfigAttribute {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let attribute = match value.to_ascii_lowercase().as_str() {
+            "bold" => Attribute::Bold,
+            "dim" => Attribute::Dim,
+            "italic" => Attribute::Italic,
+            "underlined" => Attribute::Underlined,
+            "slow_blink" => Attribute::SlowBlink,
+            "rapid_blink" => Attribute::RapidBlink,
+            "reverse" => Attribute::Reverse,
+            "hidden" => Attribute::Hidden,
+            "crossed_out" => Attribute::CrossedOut,
+            _ => return Err(format!("unknown attribute: '{value}'")),
+        };
+        Ok(ConfigAttribute(attribute))
+    }
+}
+
+impl TryFrom<String> for KeyConfig {
+    type Error = String;
+
+    /// Parse a key like `ctrl-l`, `alt-up`, or `f5`.
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        use rustyline::{KeyCode, KeyEvent, Modifiers};
+
+        let mut modifiers = Modifiers::NONE;
+        let mut rest = value.trim();
+        while let Some((modifier, key)) = rest.split_once('-')
+            && !key.is_empty()
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => Modifiers::CTRL,
+                "alt" | "meta" => Modifiers::ALT,
+                "shift" => Modifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{modifier}' in key: '{value}'")),
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "insert" => KeyCode::Insert,
+                "delete" => KeyCode::Delete,
+                "backspace" => KeyCode::Backspace,
+                "tab" => KeyCode::Tab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key: '{value}'")),
+                },
+            },
+        };
+        Ok(KeyConfig(KeyEvent::normalize(KeyEvent(code, modifiers))))
+    }
+}
+
+pub fn default_config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_default()
+        .join("h5sh")
+        .join("config.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_empty() {
+        let config = Config::parse("").unwrap();
+        assert!(config.style.dataset.is_none());
+        assert_eq!(config.prompt.format, PromptConfig::default().format);
+        assert_eq!(config.history.size, 1000);
+        assert_eq!(config.history.dedup, Dedup::Consecutive);
+        assert!(!config.history.all_files);
+        assert_eq!(config.editor.edit_mode, EditMode::Emacs);
+        assert_eq!(config.editor.completion, CompletionMode::Prefix);
+        assert!(config.default_flags.is_empty());
+        assert!(config.keybindings.is_empty());
+    }
+
+    #[test]
+    fn parse_style_items() {
+        let config = Config::parse(
+            r##"
+[style]
+dataset = { fg = "dark_cyan" }
+group = { fg = "#10a0ff", bg = "208", attributes = ["bold", "underlined"] }
+
+[style.editor]
+command = { attributes = ["italic"] }
+"##,
+        )
+        .unwrap();
+
+        let dataset = config.style.dataset.unwrap();
+        assert_eq!(dataset.fg, Some(ConfigColor(Color::DarkCyan)));
+        assert_eq!(dataset.bg, None);
+        assert!(dataset.attributes.is_empty());
+
+        let group = config.style.group.unwrap();
+        assert_eq!(
+            group.fg,
+            Some(ConfigColor(Color::Rgb {
+                r: 16,
+                g: 160,
+                b: 255
+            }))
+        );
+        assert_eq!(group.bg, Some(ConfigColor(Color::AnsiValue(208))));
+        assert_eq!(
+            group.attributes,
+            vec![
+                ConfigAttribute(Attribute::Bold),
+                ConfigAttribute(Attribute::Underlined)
+            ]
+        );
+
+        let command = config.style.editor.command.unwrap();
+        assert_eq!(command.attributes, vec![ConfigAttribute(Attribute::Italic)]);
+    }
+
+    #[test]
+    fn parse_bad_color() {
+        assert!(Config::parse("[style]\ndataset = { fg = \"not-a-color\" }").is_err());
+        assert!(Config::parse("[style]\ndataset = { fg = \"#12345\" }").is_err());
+    }
+
+    #[test]
+    fn parse_unknown_key() {
+        assert!(Config::parse("[style]\nfolder = { fg = \"red\" }").is_err());
+    }
+
+    #[test]
+    fn parse_prompt() {
+        let config = Config::parse(
+            r#"
+[prompt]
+format = "{group}{nx_class} {char} "
+char = ">"
+continuation = "... "
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.prompt.format.0,
+            vec![
+                PromptSegment::Module(PromptModule::WorkingGroup),
+                PromptSegment::Module(PromptModule::NxClass),
+                PromptSegment::Literal(" ".to_string()),
+                PromptSegment::Module(PromptModule::Char),
+                PromptSegment::Literal(" ".to_string()),
+            ]
+        );
+        assert_eq!(config.prompt.char, ">");
+        assert_eq!(config.prompt.continuation, "... ");
+    }
+
+    #[test]
+    fn parse_prompt_format_escaped_braces() {
+        let format = PromptFormat::try_from("{{{file}}}:".to_string()).unwrap();
+        assert_eq!(
+            format.0,
+            vec![
+                PromptSegment::Literal("{".to_string()),
+                PromptSegment::Module(PromptModule::FileName),
+                PromptSegment::Literal("}:".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_prompt_format_bad() {
+        assert!(PromptFormat::try_from("{file".to_string()).is_err());
+        assert!(PromptFormat::try_from("file}".to_string()).is_err());
+        assert!(PromptFormat::try_from("{folder}".to_string()).is_err());
+    }
+
+    #[test]
+    fn parse_behaviour() {
+        let config = Config::parse(
+            r#"
+[history]
+path = "/tmp/h5sh-history.txt"
+size = 20
+dedup = "all"
+all_files = true
+
+[editor]
+edit_mode = "vi"
+completion = "fuzzy"
+
+[default_flags]
+ls = "-l"
+
+[keybindings]
+alt-up = "cd .."
+ctrl-l = "ls"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.history.path(),
+            PathBuf::from("/tmp/h5sh-history.txt")
+        );
+        assert_eq!(config.history.legacy_path(), None);
+        assert_eq!(config.history.size, 20);
+        assert_eq!(config.history.dedup, Dedup::All);
+        assert!(config.history.all_files);
+        assert_eq!(config.editor.edit_mode, EditMode::Vi);
+        assert_eq!(config.editor.completion, CompletionMode::Fuzzy);
+        assert_eq!(
+            config.default_flags.get("ls").map(String::as_str),
+            Some("-l")
+        );
+        assert_eq!(
+            config
+                .keybindings
+                .get(&KeyConfig::try_from("alt-up".to_string()).unwrap())
+                .map(String::as_str),
+            Some("cd ..")
+        );
+        assert_eq!(config.keybindings.len(), 2);
+    }
+
+    #[test]
+    fn parse_keys() {
+        use rustyline::{KeyCode, KeyEvent, Modifiers};
+
+        let parse = |key: &str| KeyConfig::try_from(key.to_string()).map(|key| key.0);
+        assert_eq!(parse("ctrl-l"), Ok(KeyEvent::ctrl('L')));
+        assert_eq!(parse("Ctrl-L"), Ok(KeyEvent::ctrl('L')));
+        assert_eq!(parse("alt-up"), Ok(KeyEvent(KeyCode::Up, Modifiers::ALT)));
+        assert_eq!(
+            parse("ctrl-alt-x"),
+            Ok(KeyEvent(KeyCode::Char('X'), Modifiers::CTRL_ALT))
+        );
+        assert_eq!(parse("alt--"), Ok(KeyEvent::alt('-')));
+        assert_eq!(parse("f5"), Ok(KeyEvent(KeyCode::F(5), Modifiers::NONE)));
+        assert_eq!(
+            parse("shift-tab"),
+            Ok(KeyEvent(KeyCode::BackTab, Modifiers::NONE))
+        );
+    }
+
+    #[test]
+    fn parse_bad_keys() {
+        assert!(KeyConfig::try_from("super-l".to_string()).is_err());
+        assert!(KeyConfig::try_from("ctrl-nope".to_string()).is_err());
+        assert!(KeyConfig::try_from("f99".to_string()).is_err());
+        assert!(KeyConfig::try_from("".to_string()).is_err());
+    }
+}
@@ -1,10 +1,10 @@
-use crate::h5::{self, PartialData};
+use crate::h5::{self, H5Dataset, PartialData};
 use crate::output::Printer;
 use bumpalo::{Bump, collections::String as BumpString};
 use crossterm::{ExecutableCommand, style::Print};
 use hdf5::{H5Type, types::TypeDescriptor};
 use std::fmt::Display;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
 pub fn load_and_format_data<'alloc>(
     container: &impl Deref<Target = hdf5::Container>,
@@ -45,6 +45,67 @@ pub fn load_and_format_data<'alloc>(
     }
 }
 
+/// Load a range of rows, i.e., indices along the first axis, and format each on its own line.
+pub fn load_and_format_rows<'alloc>(
+    dataset: &H5Dataset,
+    rows: Range<usize>,
+    printer: &Printer,
+    bump: &'alloc Bump,
+) -> h5::Result<BumpString<'alloc>> {
+    use hdf5::types::{FixedAscii, FixedUnicode, FloatSize, IntSize, VarLenAscii, VarLenUnicode};
+
+    const MAX_N: usize = 1024;
+    match dataset.dtype()?.to_descriptor()? {
+        TypeDescriptor::VarLenUnicode => format_rows::<VarLenUnicode>(dataset, rows, bump),
+        TypeDescriptor::VarLenAscii => format_rows::<VarLenAscii>(dataset, rows, bump),
+        TypeDescriptor::FixedUnicode(n) if n <= MAX_N => {
+            format_rows::<FixedUnicode<MAX_N>>(dataset, rows, bump)
+        }
+        TypeDescriptor::FixedAscii(n) if n <= MAX_N => {
+            format_rows::<FixedAscii<MAX_N>>(dataset, rows, bump)
+        }
+        TypeDescriptor::FixedUnicode(_) | TypeDescriptor::FixedAscii(_) => Err(h5::H5Error::Other(
+            format!("Can only read fixed-length strings of up to {MAX_N} bytes"),
+        )),
+        TypeDescriptor::Float(FloatSize::U8) => format_rows::<f64>(dataset, rows, bump),
+        // f16 is unstable, so approximate using f32
+        TypeDescriptor::Float(_) => format_rows::<f32>(dataset, rows, bump),
+        TypeDescriptor::Integer(IntSize::U8) => format_rows::<i64>(dataset, rows, bump),
+        TypeDescriptor::Integer(IntSize::U4) => format_rows::<i32>(dataset, rows, bump),
+        TypeDescriptor::Integer(IntSize::U2) => format_rows::<i16>(dataset, rows, bump),
+        TypeDescriptor::Integer(IntSize::U1) => format_rows::<i8>(dataset, rows, bump),
+        TypeDescriptor::Unsigned(IntSize::U8) => format_rows::<u64>(dataset, rows, bump),
+        TypeDescriptor::Unsigned(IntSize::U4) => format_rows::<u32>(dataset, rows, bump),
+        TypeDescriptor::Unsigned(IntSize::U2) => format_rows::<u16>(dataset, rows, bump),
+        TypeDescriptor::Unsigned(IntSize::U1) => format_rows::<u8>(dataset, rows, bump),
+        TypeDescriptor::Boolean => format_rows::<bool>(dataset, rows, bump),
+        descriptor => Err(h5::H5Error::Other(format!(
+            "dtype not supported: {}",
+            printer.format_dtype(&descriptor, bump)
+        ))),
+    }
+}
+
+fn format_rows<'alloc, T: H5Type + Display>(
+    dataset: &H5Dataset,
+    rows: Range<usize>,
+    bump: &'alloc Bump,
+) -> h5::Result<BumpString<'alloc>> {
+    use std::fmt::Write;
+
+    let content = dataset.read_rows::<T>(rows)?;
+    let mut out = BumpString::new_in(bump);
+    for (index, row) in content.outer_iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        if write!(&mut out, "{row}").is_err() {
+            let _ = write!(&mut out, "<failed write>");
+        }
+    }
+    Ok(out)
+}
+
 mod load_and_format {
     use super::*;
     use crate::h5::H5Error;
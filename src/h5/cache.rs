@@ -1,6 +1,9 @@
 use anyhow::{Result, bail};
+use hdf5::types::TypeDescriptor;
 use indexmap::IndexMap;
 use smallvec::SmallVec;
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, hash_map::Entry};
 
 use super::file::H5File;
 use super::path::H5Path;
@@ -9,6 +12,7 @@ use super::path::H5Path;
 #[derive(Clone, Debug, Default)]
 pub struct FileCache<Value> {
     objects: IndexMap<H5Path, CacheEntry<Value>>,
+    attr_names: HashMap<CacheEntryId, Vec<String>>,
 }
 
 pub type H5FileCache = FileCache<CacheValue>;
@@ -23,7 +27,6 @@ pub enum CacheEntry<Value> {
         children: Option<SmallVec<CacheEntryId, 4>>,
     },
     Leaf {
-        #[allow(dead_code)]
         value: Value,
     },
 }
@@ -31,10 +34,22 @@ pub enum CacheEntry<Value> {
 use crate::h5::H5Object;
 pub use CacheEntry::{Group, Leaf};
 
+/// What the cache knows about whether an object exists.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Existence {
+    Group,
+    Leaf,
+    /// The object does not exist in the file.
+    Missing,
+    /// The cache does not contain enough information to tell.
+    Unknown,
+}
+
 impl<Value> FileCache<Value> {
     pub fn new() -> Self {
         Self {
             objects: IndexMap::with_capacity(16),
+            attr_names: HashMap::new(),
         }
     }
 
@@ -103,6 +118,104 @@ impl<Value> FileCache<Value> {
         let parent = self.get_mut(parent).unwrap();
         parent.insert_children(child_ids)
     }
+
+    /// Determine whether the object at an absolute path exists without loading anything.
+    pub fn existence(&self, path: &H5Path) -> Existence {
+        if let Some(entry) = self.get(path) {
+            return if entry.is_leaf() {
+                Existence::Leaf
+            } else {
+                Existence::Group
+            };
+        }
+        // The deepest cached ancestor tells whether its children, including the next
+        // segment of the path, are known.
+        let mut ancestor = path.parent();
+        loop {
+            match self.get(&ancestor) {
+                Some(Group { children: None, .. }) => return Existence::Unknown,
+                Some(_) => return Existence::Missing,
+                None if ancestor.segments().next().is_none() => return Existence::Unknown,
+                None => ancestor = ancestor.parent(),
+            }
+        }
+    }
+
+    /// Return the names of the attributes of an object if they are in the cache.
+    pub fn cached_attr_names(&self, path: &H5Path) -> Option<&[String]> {
+        let index = self.objects.get_index_of(&path.normalized())?;
+        self.attr_names
+            .get(&CacheEntryId::from(index))
+            .map(Vec::as_slice)
+    }
+
+    /// Return the names of the attributes of an object.
+    ///
+    /// Loads the names if they are not in the cache yet.
+    /// Returns `None` if the path is not in the cache or loading fails.
+    pub fn attr_names_of<LoadAttrNames>(
+        &mut self,
+        path: &H5Path,
+        load_attr_names: LoadAttrNames,
+    ) -> Option<&[String]>
+    where
+        LoadAttrNames: Fn(&Value) -> super::Result<Vec<String>>,
+    {
+        let (index, _, entry) = self.objects.get_full(&path.normalized())?;
+        let names = match self.attr_names.entry(CacheEntryId::from(index)) {
+            Entry::Occupied(occupied) => occupied.into_mut(),
+            Entry::Vacant(vacant) => {
+                let value = match entry {
+                    Group { value, .. } | Leaf { value } => value,
+                };
+                vacant.insert(load_attr_names(value).ok()?)
+            }
+        };
+        Some(names.as_slice())
+    }
+
+    /// Return the paths of all children of a group and whether they are groups.
+    ///
+    /// Loads the children if they are not in the cache yet.
+    /// Returns `None` if the path is not a group in the cache or loading fails.
+    pub fn children_of<LoadChildren, Children>(
+        &mut self,
+        path: &H5Path,
+        load_children: LoadChildren,
+    ) -> Option<Vec<(H5Path, bool)>>
+    where
+        LoadChildren: Fn(&Value) -> super::Result<Children>,
+        Children: IntoIterator<Item = (H5Path, Value, bool)>,
+    {
+        let loaded = match self.get(path)? {
+            Leaf { .. } => return None,
+            Group {
+                children: Some(_), ..
+            } => None,
+            Group {
+                value,
+                children: None,
+            } => Some(load_children(value).ok()?.into_iter().collect::<Vec<_>>()),
+        };
+        if let Some(loaded) = loaded {
+            self.insert_children(path, loaded).ok()?;
+        }
+
+        let Some(Group {
+            children: Some(children),
+            ..
+        }) = self.get(path)
+        else {
+            return None;
+        };
+        Some(
+            children
+                .iter()
+                .filter_map(|id| self.get_key_value(*id))
+                .map(|(path, entry)| (path.clone(), !entry.is_leaf()))
+                .collect(),
+        )
+    }
 }
 
 impl H5FileCache {
@@ -115,10 +228,49 @@ impl H5FileCache {
 
         Ok(cache)
     }
+
+    /// Return a function that loads the attribute names of a cached object from a file.
+    pub fn attr_name_loader(
+        file: &H5File,
+    ) -> impl Fn(&CacheValue) -> super::Result<Vec<String>> + '_ {
+        move |value: &CacheValue| match file.load(*value.location_token())? {
+            H5Object::Group(group) => group.attr_names(),
+            H5Object::Dataset(dataset) => dataset.attr_names(),
+            H5Object::Attribute(_) => Err(super::H5Error::Other(
+                "Attributes do not have attributes".into(),
+            )),
+        }
+    }
+
+    /// Return a function that identifies the groups that `**` in globs descends into.
+    ///
+    /// Like `find`, globs do not follow soft links.
+    pub fn glob_identifier() -> impl Fn(&CacheValue) -> Option<hdf5::LocationToken> {
+        |value: &CacheValue| value.is_hard_link().then(|| *value.location_token())
+    }
+
+    /// Return a function that loads the children of a cached group from a file.
+    pub fn child_loader(
+        file: &H5File,
+    ) -> impl Fn(&CacheValue) -> super::Result<Vec<(H5Path, CacheValue, bool)>> + '_ {
+        move |parent: &CacheValue| match file.load(*parent.location_token())? {
+            H5Object::Group(group) => Ok(group
+                .load_child_locations()?
+                .into_iter()
+                .map(|(path, location_info, link_type)| {
+                    (
+                        path,
+                        CacheValue::from_location_info(location_info, link_type),
+                        matches!(location_info.loc_type, hdf5::LocationType::Group),
+                    )
+                })
+                .collect()),
+            _ => Err(super::H5Error::Other("Not a group".into())),
+        }
+    }
 }
 
 impl<Value> CacheEntry<Value> {
-    #[cfg(test)] // currently only used in tests
     pub fn value(&self) -> &Value {
         match self {
             Group { value, .. } => value,
@@ -213,22 +365,109 @@ impl<Entry> CacheKey<Entry> for CacheEntryId {
 #[derive(Debug)]
 pub struct CacheValue {
     location_info: hdf5::LocationInfo,
+    /// Type of the link that the object was loaded through.
+    link_type: hdf5::LinkType,
+    /// Loaded on first use, `None` if the object is not a dataset.
+    /// Not used for files opened for SWMR because datasets can grow.
+    dataset_info: OnceCell<Option<ContainerInfo>>,
+    /// Attributes by name, loaded on first use, `None` if loading failed.
+    attr_info: RefCell<HashMap<String, Option<ContainerInfo>>>,
+}
+
+/// Shape and dtype of a dataset or attribute.
+#[derive(Clone, Debug)]
+pub struct ContainerInfo {
+    pub shape: Vec<usize>,
+    /// `None` if the dtype cannot be described.
+    pub dtype: Option<TypeDescriptor>,
+}
+
+impl ContainerInfo {
+    fn from_container(container: &hdf5::Container) -> Self {
+        Self {
+            shape: container.shape(),
+            dtype: container
+                .dtype()
+                .and_then(|dtype| dtype.to_descriptor())
+                .ok(),
+        }
+    }
 }
 
 impl CacheValue {
     pub fn from_h5object(object: &H5Object) -> super::Result<Self> {
-        Ok(Self {
-            location_info: object.location_info()?,
-        })
+        Ok(Self::from_location_info(
+            object.location_info()?,
+            hdf5::LinkType::Hard,
+        ))
     }
 
-    pub fn from_location_info(location_info: hdf5::LocationInfo) -> Self {
-        Self { location_info }
+    pub fn from_location_info(
+        location_info: hdf5::LocationInfo,
+        link_type: hdf5::LinkType,
+    ) -> Self {
+        Self {
+            location_info,
+            link_type,
+            dataset_info: OnceCell::new(),
+            attr_info: RefCell::new(HashMap::new()),
+        }
     }
 
     pub fn location_token(&self) -> &hdf5::LocationToken {
         &self.location_info.token
     }
+
+    /// Return true if the object was loaded through a hard link.
+    pub fn is_hard_link(&self) -> bool {
+        self.link_type == hdf5::LinkType::Hard
+    }
+
+    /// Return the shape and dtype if the object is a dataset.
+    ///
+    /// Loads them from the file on first use.
+    /// In SWMR mode, they are always loaded from the file to see new rows.
+    pub fn dataset_info(&self, file: &H5File) -> Option<ContainerInfo> {
+        if file.is_swmr() {
+            return self.load_dataset_info(file, true);
+        }
+        self.dataset_info
+            .get_or_init(|| self.load_dataset_info(file, false))
+            .clone()
+    }
+
+    fn load_dataset_info(&self, file: &H5File, refresh: bool) -> Option<ContainerInfo> {
+        match file.load(*self.location_token()).ok()? {
+            H5Object::Dataset(dataset) => {
+                if refresh {
+                    dataset.refresh().ok()?;
+                }
+                Some(ContainerInfo::from_container(dataset.underlying()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the shape and dtype of an attribute of the object.
+    ///
+    /// Loads them from the file on first use.
+    pub fn attr_info(&self, name: &str, file: &H5File) -> Option<ContainerInfo> {
+        if let Some(info) = self.attr_info.borrow().get(name) {
+            return info.clone();
+        }
+        let attr = match file.load(*self.location_token()).ok()? {
+            H5Object::Group(group) => group.attr(name),
+            H5Object::Dataset(dataset) => dataset.attr(name),
+            H5Object::Attribute(_) => return None,
+        };
+        let info = attr
+            .ok()
+            .map(|attr| ContainerInfo::from_container(attr.underlying()));
+        self.attr_info
+            .borrow_mut()
+            .insert(name.to_string(), info.clone());
+        info
+    }
 }
 
 #[cfg(test)]
@@ -337,4 +576,77 @@ mod tests {
         let b_entry = cache.get(b).unwrap();
         assert_children(b_entry, Some(smallvec![c_id]));
     }
+
+    #[test]
+    fn attr_names_are_loaded_once() {
+        let a = H5Path::from("/a");
+        let mut cache = FileCache::<i32>::default();
+        cache.insert_leaf(&a, 6);
+        let load = |value: &i32| Ok(vec![format!("attr{value}")]);
+        assert_eq!(
+            cache.attr_names_of(&a, load),
+            Some(["attr6".to_string()].as_slice())
+        );
+        let fail = |_: &i32| -> crate::h5::Result<Vec<String>> { panic!("Do not load again!") };
+        assert_eq!(
+            cache.attr_names_of(&a, fail),
+            Some(["attr6".to_string()].as_slice())
+        );
+        assert_eq!(cache.attr_names_of(&H5Path::from("/b"), load), None);
+    }
+
+    #[test]
+    fn existence_of_cached_objects() {
+        let mut cache = FileCache::<i32>::default();
+        let root = cache.insert_group(&H5Path::root(), 0);
+        cache
+            .insert_children(
+                root,
+                [
+                    (H5Path::from("/a"), 1, false),
+                    (H5Path::from("/b"), 2, true),
+                ],
+            )
+            .unwrap();
+        assert_eq!(cache.existence(&H5Path::root()), Existence::Group);
+        assert_eq!(cache.existence(&H5Path::from("/a")), Existence::Leaf);
+        assert_eq!(cache.existence(&H5Path::from("/b/")), Existence::Group);
+    }
+
+    #[test]
+    fn existence_of_uncached_objects() {
+        let mut cache = FileCache::<i32>::default();
+        let root = cache.insert_group(&H5Path::root(), 0);
+        cache
+            .insert_children(
+                root,
+                [
+                    (H5Path::from("/a"), 1, false),
+                    (H5Path::from("/b"), 2, true),
+                ],
+            )
+            .unwrap();
+        assert_eq!(cache.existence(&H5Path::from("/c")), Existence::Missing);
+        assert_eq!(cache.existence(&H5Path::from("/c/d")), Existence::Missing);
+        assert_eq!(cache.existence(&H5Path::from("/a/d")), Existence::Missing);
+        assert_eq!(cache.existence(&H5Path::from("/b/d")), Existence::Unknown);
+        assert_eq!(cache.existence(&H5Path::from("/b/d/e")), Existence::Unknown);
+        assert_eq!(
+            FileCache::<i32>::default().existence(&H5Path::from("/a")),
+            Existence::Unknown
+        );
+    }
+
+    #[test]
+    fn cached_attr_names_does_not_load() {
+        let a = H5Path::from("/a");
+        let mut cache = FileCache::<i32>::default();
+        cache.insert_leaf(&a, 6);
+        assert_eq!(cache.cached_attr_names(&a), None);
+        cache.attr_names_of(&a, |value: &i32| Ok(vec![format!("attr{value}")]));
+        assert_eq!(
+            cache.cached_attr_names(&a),
+            Some(["attr6".to_string()].as_slice())
+        );
+    }
 }
@@ -18,6 +18,8 @@ impl Display for H5Error {
     }
 }
 
+impl std::error::Error for H5Error {}
+
 impl From<hdf5::Error> for H5Error {
     fn from(error: hdf5::Error) -> Self {
         match error {
@@ -1,28 +1,235 @@
+use super::cache::{CacheEntry, ContainerInfo, Existence, H5FileCache};
 use super::error::{H5Error, Result};
-use super::object::H5Object;
+use super::file_info::FileInfo;
+use super::object::{H5Group, H5Object};
 use super::path::H5Path;
 use crate::h5::ObjectPath;
+use hdf5::plist::FileAccess;
+use hdf5::plist::file_access::{ChunkCache, MetadataCacheConfig};
 use log::info;
-use std::path::PathBuf;
+use std::cell::{Ref, RefCell, RefMut};
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
 
+/// How to open a file.
+#[derive(Clone, Debug, Default)]
+pub struct OpenOptions {
+    /// Open for single-writer-multiple-reader access.
+    /// This allows reading a file while another program writes it.
+    pub swmr: bool,
+    pub driver: Driver,
+    /// Initial and maximum size of the metadata cache in bytes.
+    pub metadata_cache_size: Option<usize>,
+    /// Size of the raw data chunk cache of each dataset in bytes.
+    pub chunk_cache_size: Option<usize>,
+    /// Lower and upper bound of the library versions for object formats.
+    pub libver_bounds: Option<(LibraryVersion, LibraryVersion)>,
+}
+
+/// Low-level driver for file access.
+#[derive(Clone, Debug, Default)]
+pub enum Driver {
+    /// Use the default driver (sec2).
+    #[default]
+    Default,
+    Stdio,
+    /// Read the whole file into memory.
+    Core,
+    /// A file split into members of equal size.
+    /// The path must contain a printf-style integer format like `data-%05d.h5`.
+    Family {
+        /// Size of each member in bytes, `None` to use the size of the first member.
+        member_size: Option<usize>,
+    },
+    /// Metadata and raw data in separate files with the given extensions.
+    Split {
+        meta_ext: String,
+        raw_ext: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LibraryVersion {
+    Earliest,
+    V18,
+    V110,
+    V112,
+    V114,
+}
+
+impl LibraryVersion {
+    pub const LATEST: Self = Self::V114;
+
+    fn to_raw(self) -> hdf5_sys::h5f::H5F_libver_t {
+        use hdf5_sys::h5f::H5F_libver_t::*;
+        match self {
+            Self::Earliest => H5F_LIBVER_EARLIEST,
+            Self::V18 => H5F_LIBVER_V18,
+            Self::V110 => H5F_LIBVER_V110,
+            Self::V112 => H5F_LIBVER_V112,
+            Self::V114 => H5F_LIBVER_V114,
+        }
+    }
+}
+
+impl OpenOptions {
+    fn open(&self, path: &Path) -> hdf5::Result<hdf5::File> {
+        let fapl = self.access_plist()?;
+        // The hdf5 crate does not support opening files in SWMR mode, so open it directly.
+        let name = path
+            .to_str()
+            .and_then(|name| CString::new(name).ok())
+            .ok_or_else(|| format!("Invalid file name: {}", path.display()))?;
+        let mut flags = hdf5_sys::h5f::H5F_ACC_RDONLY;
+        if self.swmr {
+            flags |= hdf5_sys::h5f::H5F_ACC_SWMR_READ;
+        }
+        hdf5::sync::sync(|| {
+            let id = unsafe { hdf5_sys::h5f::H5Fopen(name.as_ptr(), flags, fapl.id()) };
+            if id < 0 {
+                return Err(hdf5::Error::query().unwrap_or_else(|err| err));
+            }
+            unsafe { hdf5::from_id(id) }
+        })
+    }
+
+    fn access_plist(&self) -> hdf5::Result<FileAccess> {
+        let mut builder = FileAccess::build();
+        match &self.driver {
+            Driver::Default => &mut builder,
+            Driver::Stdio => builder.stdio(),
+            Driver::Core => builder.core(),
+            Driver::Family { member_size } => builder.family_options(member_size.unwrap_or(0)),
+            Driver::Split { meta_ext, raw_ext } => builder.split_options(meta_ext, raw_ext),
+        };
+        if let Some(size) = self.chunk_cache_size {
+            let default = ChunkCache::default();
+            builder.chunk_cache(default.nslots, size, default.w0);
+        }
+        if let Some(size) = self.metadata_cache_size {
+            let default = MetadataCacheConfig::default();
+            builder.mdc_config(&MetadataCacheConfig {
+                set_initial_size: true,
+                initial_size: size,
+                max_size: size,
+                min_size: default.min_size.min(size),
+                ..default
+            });
+        }
+        let fapl = builder.finish()?;
+
+        // The hdf5 crate does not support versions after 1.10.
+        if let Some((low, high)) = self.libver_bounds {
+            hdf5::sync::sync(|| {
+                let status = unsafe {
+                    hdf5_sys::h5p::H5Pset_libver_bounds(fapl.id(), low.to_raw(), high.to_raw())
+                };
+                if status < 0 {
+                    return Err(hdf5::Error::query().unwrap_or_else(|err| err));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(fapl)
+    }
+}
+
+/// An open HDF5 file together with a cache of its metadata.
+///
+/// The cache is shared by all commands and the line editor.
 #[derive(Debug)]
 pub struct H5File {
-    file: hdf5::File,
+    path: PathBuf,
+    options: OpenOptions,
+    /// Only `None` if reopening the file failed.
+    file: RefCell<Option<hdf5::File>>,
+    cache: RefCell<H5FileCache>,
 }
 
 impl H5File {
-    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+    pub fn open(path: PathBuf, options: OpenOptions) -> std::io::Result<Self> {
         info!("Opening file: {:}", path.display());
-        let file = hdf5::File::open(path)?;
-        Ok(Self { file })
+        let file = options.open(&path)?;
+        let h5file = Self {
+            path,
+            options,
+            file: RefCell::new(Some(file)),
+            cache: RefCell::new(H5FileCache::new()),
+        };
+        h5file.reset_cache().map_err(std::io::Error::other)?;
+        Ok(h5file)
+    }
+
+    /// Close and reopen the file and clear the cache.
+    ///
+    /// This picks up changes that other programs made to the file.
+    pub fn reopen(&self) -> Result<()> {
+        info!("Reopening file: {:}", self.path.display());
+        let mut file = self.file.borrow_mut();
+        // HDF5 shares the state of a file between all of its handles.
+        // So the file must be closed before opening it again to re-read it.
+        *file = None;
+        *file = Some(self.options.open(&self.path)?);
+        drop(file);
+        self.reset_cache()
+    }
+
+    fn reset_cache(&self) -> Result<()> {
+        // Clear first so that loading the root does not use stale entries.
+        *self.cache.borrow_mut() = H5FileCache::new();
+        let cache = H5FileCache::with_root(self)?;
+        *self.cache.borrow_mut() = cache;
+        Ok(())
+    }
+
+    fn file(&self) -> Result<Ref<'_, hdf5::File>> {
+        Ref::filter_map(self.file.borrow(), Option::as_ref)
+            .map_err(|_| H5Error::Other("The file is closed".to_string()))
+    }
+
+    /// Return the cache for modification.
+    ///
+    /// Panics if the cache is already borrowed.
+    pub fn cache(&self) -> RefMut<'_, H5FileCache> {
+        self.cache.borrow_mut()
+    }
+
+    /// Return the cache for reading, `None` if it is being modified.
+    pub fn try_cache(&self) -> Option<Ref<'_, H5FileCache>> {
+        self.cache.try_borrow().ok()
     }
 
     pub fn filename(&self) -> String {
-        self.file.filename()
+        self.path.to_string_lossy().into_owned()
+    }
+
+    /// Size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.file().map_or(0, |file| file.size())
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.file().map_or(true, |file| file.is_read_only())
+    }
+
+    /// Return true if the file was opened for single-writer-multiple-reader access.
+    pub fn is_swmr(&self) -> bool {
+        let Ok(file) = self.file() else {
+            return false;
+        };
+        let mut intent: std::ffi::c_uint = 0;
+        let status =
+            hdf5::sync::sync(|| unsafe { hdf5_sys::h5f::H5Fget_intent(file.id(), &mut intent) });
+        status >= 0 && intent & hdf5_sys::h5f::H5F_ACC_SWMR_READ != 0
+    }
+
+    /// Return file-level properties.
+    pub fn info(&self) -> Result<FileInfo> {
+        FileInfo::load(&*self.file()?, &self.path, self.is_swmr())
     }
 
     pub fn load<L: LocationSpec>(&self, location: L) -> Result<H5Object> {
-        let location = location.into_location(&self.file)?;
+        let location = location.into_location(self)?;
         H5Object::from_location(H5Path::from(location.name()), &location)
     }
 
@@ -42,21 +249,137 @@ impl H5File {
             }
         }
     }
+
+    /// Load a group and its ancestors into the cache.
+    pub fn cache_group(&self, path: &H5Path) {
+        let Ok(mut cache) = self.cache.try_borrow_mut() else {
+            return;
+        };
+        let load_children = H5FileCache::child_loader(self);
+        let mut group = H5Path::root();
+        cache.children_of(&group, &load_children);
+        for segment in path.resolve().segments() {
+            group.push(segment);
+            if cache.children_of(&group, &load_children).is_none() {
+                return;
+            }
+        }
+    }
+
+    /// Load the children of a group.
+    ///
+    /// Uses the cache to find the children and loads them into the cache if needed.
+    pub fn load_children(&self, group: &H5Group) -> Result<Vec<H5Object>> {
+        self.cache_group(group.path());
+        let children =
+            self.cache.try_borrow_mut().ok().and_then(|mut cache| {
+                cache.children_of(group.path(), H5FileCache::child_loader(self))
+            });
+        let Some(children) = children else {
+            return group.load_children();
+        };
+        children
+            .into_iter()
+            .map(|(path, _)| self.load(&path))
+            .collect()
+    }
+
+    /// Return the names of the attributes of an object.
+    ///
+    /// Uses the cache if the object is in it.
+    pub fn attr_names(&self, object: &H5Object) -> Result<Vec<String>> {
+        let path = match object {
+            H5Object::Group(group) => group.path(),
+            H5Object::Dataset(dataset) => dataset.path(),
+            H5Object::Attribute(_) => {
+                return Err(H5Error::Other("Attributes do not have attributes".into()));
+            }
+        };
+        let cached = self.cache.try_borrow_mut().ok().and_then(|mut cache| {
+            cache
+                .attr_names_of(path, H5FileCache::attr_name_loader(self))
+                .map(<[String]>::to_vec)
+        });
+        if let Some(names) = cached {
+            return Ok(names);
+        }
+        match object {
+            H5Object::Group(group) => group.attr_names(),
+            H5Object::Dataset(dataset) => dataset.attr_names(),
+            H5Object::Attribute(_) => {
+                Err(H5Error::Other("Attributes do not have attributes".into()))
+            }
+        }
+    }
+
+    /// Return the shape and dtype of a dataset if it is in the cache.
+    pub fn dataset_info(&self, path: &H5Path) -> Option<ContainerInfo> {
+        let cache = self.try_cache()?;
+        match cache.get(path)? {
+            CacheEntry::Leaf { value } => value.dataset_info(self),
+            CacheEntry::Group { .. } => None,
+        }
+    }
+
+    /// Return the shape and dtype of an attribute if its object is in the cache.
+    pub fn attr_info(&self, path: &H5Path, name: &str) -> Option<ContainerInfo> {
+        let cache = self.try_cache()?;
+        cache.get(path)?.value().attr_info(name, self)
+    }
+
+    /// Return the number of children of a group if it is in the cache.
+    ///
+    /// Loads the children into the cache if needed.
+    pub fn n_children(&self, path: &H5Path) -> Option<usize> {
+        let mut cache = self.cache.try_borrow_mut().ok()?;
+        if let CacheEntry::Group {
+            children: Some(children),
+            ..
+        } = cache.get(path)?
+        {
+            return Some(children.len());
+        }
+        cache
+            .children_of(path, H5FileCache::child_loader(self))
+            .map(|children| children.len())
+    }
+
+    /// Look up an absolute path in the cache.
+    ///
+    /// Returns `None` if the cache does not know the path.
+    fn cached_token(&self, path: &H5Path) -> Option<Result<hdf5::LocationToken>> {
+        if !path.is_absolute() {
+            return None;
+        }
+        let path = path.resolve();
+        let cache = self.try_cache()?;
+        match cache.existence(&path) {
+            Existence::Group | Existence::Leaf => {
+                Some(Ok(*cache.get(&path)?.value().location_token()))
+            }
+            Existence::Missing => Some(Err(H5Error::NotFound(path))),
+            Existence::Unknown => None,
+        }
+    }
 }
 
 pub trait LocationSpec {
-    fn into_location(self, file: &hdf5::File) -> Result<hdf5::Location>;
+    fn into_location(self, file: &H5File) -> Result<hdf5::Location>;
 }
 
 impl LocationSpec for hdf5::LocationToken {
-    fn into_location(self, file: &hdf5::File) -> Result<hdf5::Location> {
-        Ok(file.open_by_token(self)?)
+    fn into_location(self, file: &H5File) -> Result<hdf5::Location> {
+        Ok(file.file()?.open_by_token(self)?)
     }
 }
 
 impl LocationSpec for &H5Path {
-    fn into_location(self, file: &hdf5::File) -> Result<hdf5::Location> {
-        match file.loc_info_by_name(self.as_raw()).map_err(|e| e.into()) {
+    fn into_location(self, file: &H5File) -> Result<hdf5::Location> {
+        if let Some(token) = file.cached_token(self) {
+            return token?.into_location(file);
+        }
+        let location_info = file.file()?.loc_info_by_name(self.as_raw());
+        match location_info.map_err(|e| e.into()) {
             Ok(location_info) => location_info.token.into_location(file),
             Err(H5Error::NotFound(_)) => Err(H5Error::NotFound(self.clone())),
             Err(err) => Err(err),
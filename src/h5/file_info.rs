@@ -0,0 +1,211 @@
+use super::error::{H5Error, Result};
+use hdf5::file::FileDriver;
+use hdf5_sys::h5f::{self, H5F_libver_t};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// File-level properties of an open file.
+#[derive(Clone, Debug)]
+pub struct FileInfo {
+    pub path: PathBuf,
+    /// Size of the file on disk, `None` if it cannot be determined.
+    pub disk_size: Option<u64>,
+    /// Size of the file as seen by HDF5, `None` if it is meaningless for the driver.
+    pub size: Option<u64>,
+    pub driver: &'static str,
+    pub swmr: bool,
+    pub superblock_version: u32,
+    pub superblock_size: u64,
+    pub superblock_ext_size: u64,
+    pub userblock_size: u64,
+    pub libver_low: &'static str,
+    pub libver_high: &'static str,
+    pub free_space: u64,
+    pub free_space_version: u32,
+    /// Size of the metadata of the free-space manager.
+    pub free_space_meta_size: u64,
+    pub open_objects: OpenObjects,
+    pub status: FileStatus,
+}
+
+/// Number of open objects in the file, including those opened by h5sh itself.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OpenObjects {
+    pub files: usize,
+    pub groups: usize,
+    pub datasets: usize,
+    pub datatypes: usize,
+    pub attributes: usize,
+}
+
+/// Whether a file was closed cleanly according to the status flags of the superblock.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileStatus {
+    Closed,
+    /// Open for writing, or the writer did not close the file properly.
+    OpenForWriting,
+    /// Open for writing in single-writer-multiple-reader mode, or not closed properly.
+    OpenForSwmrWriting,
+    /// Superblocks before version 2 do not record the status.
+    NotRecorded,
+    /// The superblock cannot be read directly with the driver of the file.
+    NotAvailable,
+}
+
+const SIGNATURE: &[u8; 8] = b"\x89HDF\r\n\x1a\n";
+
+impl FileInfo {
+    pub(super) fn load(file: &hdf5::File, path: &Path, swmr: bool) -> Result<Self> {
+        let id = file.id();
+        let mut info = std::mem::MaybeUninit::<h5f::H5F_info2_t>::uninit();
+        let status = hdf5::sync::sync(|| unsafe { h5f::H5Fget_info2(id, info.as_mut_ptr()) });
+        if status < 0 {
+            return Err(H5Error::Other("Failed to get file info".to_string()));
+        }
+        // SAFETY: H5Fget_info2 succeeded and initialized the struct.
+        let info = unsafe { info.assume_init() };
+
+        let access = file.access_plist()?;
+        let driver = access.driver();
+        let (libver_low, libver_high) = libver_bounds(access.id())?;
+        let userblock_size = file.userblock();
+        let member = |ext: &str| PathBuf::from(format!("{}{ext}", path.display()));
+        // The path of a family or multi file is not the path of the superblock,
+        // so its status is not read.
+        let (size, disk_size, superblock_path) = match &driver {
+            // The multi drivers place the members at distant addresses
+            // so the size reported by HDF5 is huge.
+            FileDriver::Split(split) => (
+                None,
+                [&split.meta_ext, &split.raw_ext]
+                    .into_iter()
+                    .map(|ext| file_size(&member(ext)))
+                    .sum(),
+                Some(member(&split.meta_ext)),
+            ),
+            FileDriver::Multi(_) => (None, None, None),
+            FileDriver::Family(_) => (Some(file.size()), file_size(path), None),
+            _ => (Some(file.size()), file_size(path), Some(path.to_path_buf())),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            disk_size,
+            size,
+            driver: driver_name(&driver),
+            swmr,
+            superblock_version: info.super_.version,
+            superblock_size: info.super_.super_size,
+            superblock_ext_size: info.super_.super_ext_size,
+            userblock_size,
+            libver_low,
+            libver_high,
+            free_space: file.free_space(),
+            free_space_version: info.free.version,
+            free_space_meta_size: info.free.meta_size,
+            open_objects: OpenObjects::count(id),
+            status: superblock_path
+                .and_then(|path| read_status(&path, userblock_size))
+                .unwrap_or(FileStatus::NotAvailable),
+        })
+    }
+}
+
+impl OpenObjects {
+    fn count(id: hdf5_sys::h5i::hid_t) -> Self {
+        let count = |types| {
+            let n = hdf5::sync::sync(|| unsafe { h5f::H5Fget_obj_count(id, types) });
+            usize::try_from(n).unwrap_or(0)
+        };
+        Self {
+            files: count(h5f::H5F_OBJ_FILE),
+            groups: count(h5f::H5F_OBJ_GROUP),
+            datasets: count(h5f::H5F_OBJ_DATASET),
+            datatypes: count(h5f::H5F_OBJ_DATATYPE),
+            attributes: count(h5f::H5F_OBJ_ATTR),
+        }
+    }
+}
+
+fn libver_bounds(fapl: hdf5_sys::h5i::hid_t) -> Result<(&'static str, &'static str)> {
+    let mut low = H5F_libver_t::H5F_LIBVER_EARLIEST;
+    let mut high = H5F_libver_t::H5F_LIBVER_EARLIEST;
+    let status = hdf5::sync::sync(|| unsafe {
+        hdf5_sys::h5p::H5Pget_libver_bounds(fapl, &mut low, &mut high)
+    });
+    if status < 0 {
+        return Err(H5Error::Other(
+            "Failed to get library version bounds".to_string(),
+        ));
+    }
+    Ok((libver_name(low), libver_name(high)))
+}
+
+fn libver_name(libver: H5F_libver_t) -> &'static str {
+    match libver {
+        H5F_libver_t::H5F_LIBVER_EARLIEST => "earliest",
+        H5F_libver_t::H5F_LIBVER_V18 => "v1.8",
+        H5F_libver_t::H5F_LIBVER_V110 => "v1.10",
+        H5F_libver_t::H5F_LIBVER_V112 => "v1.12",
+        H5F_libver_t::H5F_LIBVER_V114 => "v1.14",
+        _ => "unknown",
+    }
+}
+
+fn driver_name(driver: &FileDriver) -> &'static str {
+    match driver {
+        FileDriver::Sec2 => "sec2",
+        FileDriver::Stdio => "stdio",
+        FileDriver::Log => "log",
+        FileDriver::Core(_) => "core",
+        FileDriver::Family(_) => "family",
+        FileDriver::Multi(_) => "multi",
+        FileDriver::Split(_) => "split",
+    }
+}
+
+fn file_size(path: &Path) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+/// Read the status flags from the superblock on disk.
+///
+/// Only superblocks of version 2 and later record the status.
+/// Returns `None` if the superblock cannot be read.
+fn read_status(path: &Path, userblock_size: u64) -> Option<FileStatus> {
+    let mut file = std::fs::File::open(path).ok()?;
+    file.seek(SeekFrom::Start(userblock_size)).ok()?;
+    let mut buffer = [0u8; 12];
+    file.read_exact(&mut buffer).ok()?;
+    if &buffer[..8] != SIGNATURE {
+        return None;
+    }
+    if buffer[8] < 2 {
+        return Some(FileStatus::NotRecorded);
+    }
+    Some(status_from_flags(buffer[11]))
+}
+
+fn status_from_flags(flags: u8) -> FileStatus {
+    const WRITE_ACCESS: u8 = 0x01;
+    const SWMR_WRITE_ACCESS: u8 = 0x04;
+    if flags & SWMR_WRITE_ACCESS != 0 {
+        FileStatus::OpenForSwmrWriting
+    } else if flags & WRITE_ACCESS != 0 {
+        FileStatus::OpenForWriting
+    } else {
+        FileStatus::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_flags() {
+        assert_eq!(status_from_flags(0), FileStatus::Closed);
+        assert_eq!(status_from_flags(0x01), FileStatus::OpenForWriting);
+        assert_eq!(status_from_flags(0x05), FileStatus::OpenForSwmrWriting);
+    }
+}
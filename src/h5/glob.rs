@@ -0,0 +1,679 @@
+//! Shell-style glob patterns for paths in a file.
+//!
+//! Supported are `*` (any sequence of characters in a name), `?` (any single character),
+//! `[abc]` / `[a-z]` / `[!a-z]` (character classes), and `**` (any number of groups).
+//! Special characters can be quoted with `'...'`, `"..."`, or a backslash.
+//! Backslashes also escape characters in double quotes but not in single quotes.
+
+use super::{FileCache, H5Error, H5Path, Result};
+
+/// Return true if `word` contains unquoted glob characters.
+pub fn is_pattern(word: &str) -> bool {
+    Pattern::new(word).segments.iter().any(Segment::is_glob)
+}
+
+/// Remove quotes and backslash escapes from a word.
+pub fn unquote(word: &str) -> String {
+    lex(word).into_iter().map(|(c, _)| c).collect()
+}
+
+/// Escape all characters in a name that have a special meaning in the shell.
+///
+/// The result is interpreted literally by the shell, i.e., `unquote(&escape(name)) == name`
+/// and it does not contain any glob patterns.
+pub fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_whitespace() || matches!(c, '\'' | '"' | '\\' | '$' | '*' | '?' | '[' | '@') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Return the quote char if `word` ends inside a quoted string.
+pub fn unclosed_quote(word: &str) -> Option<char> {
+    let mut quote = None;
+    let mut it = word.chars();
+    while let Some(c) = it.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some('\'') => {}
+            _ if c == '\\' => {
+                it.next();
+            }
+            Some(_) => {}
+            None if matches!(c, '\'' | '"') => quote = Some(c),
+            None => {}
+        }
+    }
+    quote
+}
+
+/// Split a word into a location and an attribute name at the first unquoted `@`.
+pub fn split_attr(word: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in word.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote != Some('\'') {
+            escaped = true;
+        } else if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+        } else {
+            match c {
+                '\'' | '"' => quote = Some(c),
+                '@' => return (&word[..i], Some(&word[i + 1..])),
+                _ => {}
+            }
+        }
+    }
+    (word, None)
+}
+
+/// Expand a glob pattern into all matching paths.
+///
+/// Relative patterns are expanded relative to `working_group` and produce
+/// relative paths.
+/// The result is sorted and never empty; a pattern without matches is an error.
+///
+/// `identify` returns an identifier of the object of a cached group that `**` may
+/// descend into, or `None` if it must not descend, e.g., for soft links.
+/// `**` never descends into a group that is also one of its ancestors.
+pub fn expand<Value, LoadChildren, Children, Identify, Id>(
+    pattern: &str,
+    working_group: &H5Path,
+    cache: &mut FileCache<Value>,
+    load_children: LoadChildren,
+    identify: Identify,
+) -> Result<Vec<H5Path>>
+where
+    LoadChildren: Fn(&Value) -> Result<Children>,
+    Children: IntoIterator<Item = (H5Path, Value, bool)>,
+    Identify: Fn(&Value) -> Option<Id>,
+    Id: PartialEq,
+{
+    let parsed = Pattern::new(pattern);
+    if !parsed.absolute {
+        // Make sure that the working group and its parents are in the cache.
+        let mut group = H5Path::root();
+        for segment in working_group.segments() {
+            cache.children_of(&group, &load_children);
+            group.push(segment);
+        }
+    }
+    let mut candidates = vec![if parsed.absolute {
+        Candidate::new(H5Path::root(), H5Path::root())
+    } else {
+        Candidate::new(working_group.clone(), H5Path::from(""))
+    }];
+
+    let n_segments = parsed.segments.len();
+    for (index, segment) in parsed.segments.iter().enumerate() {
+        let is_last = index + 1 == n_segments;
+        let mut next = Vec::new();
+        for candidate in candidates {
+            match segment {
+                Segment::Current => next.push(candidate.descend(".", candidate.path.clone())),
+                Segment::Parent => next.push(candidate.descend("..", candidate.path.parent())),
+                Segment::Recursive => {
+                    if !is_last {
+                        next.push(candidate.clone());
+                    }
+                    let mut ancestors = ancestor_ids(&candidate.path, cache, &identify);
+                    collect_descendants(
+                        &candidate,
+                        is_last,
+                        cache,
+                        &load_children,
+                        &identify,
+                        &mut ancestors,
+                        &mut next,
+                    );
+                }
+                Segment::Literal(_) | Segment::Pattern(_) => {
+                    for (path, is_group) in cache
+                        .children_of(&candidate.path, &load_children)
+                        .unwrap_or_default()
+                    {
+                        if (is_last || is_group) && segment.matches(path.name()) {
+                            next.push(candidate.descend(path.name(), path.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        candidates = next;
+    }
+
+    let mut matches: Vec<_> = candidates
+        .into_iter()
+        .map(|candidate| candidate.display)
+        .filter(|display| !display.as_raw().is_empty())
+        .collect();
+    matches.sort_by(|a, b| a.as_raw().cmp(b.as_raw()));
+    matches.dedup();
+    if matches.is_empty() {
+        Err(H5Error::Other(format!("No matches for pattern: {pattern}")))
+    } else {
+        Ok(matches)
+    }
+}
+
+/// Return the identifiers of the group at `path` and of all its parents.
+fn ancestor_ids<Value, Identify, Id>(
+    path: &H5Path,
+    cache: &FileCache<Value>,
+    identify: &Identify,
+) -> Vec<Id>
+where
+    Identify: Fn(&Value) -> Option<Id>,
+{
+    let mut group = H5Path::root();
+    let mut ids = Vec::new();
+    let mut segments = path.segments();
+    loop {
+        if let Some(id) = cache.get(&group).and_then(|entry| identify(entry.value())) {
+            ids.push(id);
+        }
+        match segments.next() {
+            Some(segment) => group.push(segment),
+            None => return ids,
+        }
+    }
+}
+
+/// `ancestors` holds the identifiers of `candidate` and its parents to detect cycles.
+fn collect_descendants<Value, LoadChildren, Children, Identify, Id>(
+    candidate: &Candidate,
+    include_leaves: bool,
+    cache: &mut FileCache<Value>,
+    load_children: &LoadChildren,
+    identify: &Identify,
+    ancestors: &mut Vec<Id>,
+    out: &mut Vec<Candidate>,
+) where
+    LoadChildren: Fn(&Value) -> Result<Children>,
+    Children: IntoIterator<Item = (H5Path, Value, bool)>,
+    Identify: Fn(&Value) -> Option<Id>,
+    Id: PartialEq,
+{
+    for (path, is_group) in cache
+        .children_of(&candidate.path, load_children)
+        .unwrap_or_default()
+    {
+        let child = candidate.descend(path.name(), path.clone());
+        if is_group {
+            let id = cache.get(&path).and_then(|entry| identify(entry.value()));
+            if let Some(id) = id
+                && !ancestors.contains(&id)
+            {
+                ancestors.push(id);
+                collect_descendants(
+                    &child,
+                    include_leaves,
+                    cache,
+                    load_children,
+                    identify,
+                    ancestors,
+                    out,
+                );
+                ancestors.pop();
+            }
+            out.push(child);
+        } else if include_leaves {
+            out.push(child);
+        }
+    }
+}
+
+fn append(display: &H5Path, name: &str) -> H5Path {
+    if display.as_raw().is_empty() {
+        H5Path::from(name)
+    } else {
+        display.join(&H5Path::from(name))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Candidate {
+    /// Absolute path in the file.
+    path: H5Path,
+    /// Path as it is shown to the user.
+    display: H5Path,
+}
+
+impl Candidate {
+    fn new(path: H5Path, display: H5Path) -> Self {
+        Self { path, display }
+    }
+
+    fn descend(&self, name: &str, path: H5Path) -> Self {
+        Self::new(path, append(&self.display, name))
+    }
+}
+
+#[derive(Debug)]
+struct Pattern {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+enum Segment {
+    Current,
+    Parent,
+    Recursive,
+    Literal(String),
+    Pattern(Vec<Token>),
+}
+
+#[derive(Debug)]
+enum Token {
+    Char(char),
+    Any,
+    One,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> Self {
+        let chars = lex(pattern);
+        let absolute = chars.first() == Some(&('/', false));
+        let segments = chars
+            .split(|&c| c == ('/', false))
+            .filter(|segment| !segment.is_empty())
+            .map(Segment::new)
+            .collect();
+        Self { absolute, segments }
+    }
+}
+
+impl Segment {
+    fn new(chars: &[(char, bool)]) -> Self {
+        let unquoted: Option<String> = chars
+            .iter()
+            .map(|&(c, quoted)| (!quoted).then_some(c))
+            .collect();
+        match unquoted.as_deref() {
+            Some(".") => return Self::Current,
+            Some("..") => return Self::Parent,
+            Some("**") => return Self::Recursive,
+            _ => {}
+        }
+
+        let tokens = tokenize(chars);
+        if tokens.iter().all(|token| matches!(token, Token::Char(_))) {
+            Self::Literal(chars.iter().map(|(c, _)| c).collect())
+        } else {
+            Self::Pattern(tokens)
+        }
+    }
+
+    fn is_glob(&self) -> bool {
+        matches!(self, Self::Recursive | Self::Pattern(_))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Literal(literal) => literal == name,
+            Self::Pattern(tokens) => match_tokens(tokens, &name.chars().collect::<Vec<_>>()),
+            _ => false,
+        }
+    }
+}
+
+/// Remove quotes and escapes, marking each character as quoted or not.
+fn lex(word: &str) -> Vec<(char, bool)> {
+    let mut chars = Vec::with_capacity(word.len());
+    let mut quote = None;
+    let mut it = word.chars();
+    while let Some(c) = it.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some('"') if c == '\\' => {
+                if let Some(escaped) = it.next() {
+                    chars.push((escaped, true));
+                }
+            }
+            Some(_) => chars.push((c, true)),
+            None => match c {
+                '\\' => {
+                    if let Some(escaped) = it.next() {
+                        chars.push((escaped, true));
+                    }
+                }
+                '\'' | '"' => quote = Some(c),
+                _ => chars.push((c, false)),
+            },
+        }
+    }
+    chars
+}
+
+fn tokenize(chars: &[(char, bool)]) -> Vec<Token> {
+    let mut tokens = Vec::with_capacity(chars.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let token = match chars[index] {
+            ('*', false) => Token::Any,
+            ('?', false) => Token::One,
+            ('[', false) => match parse_class(&chars[index + 1..]) {
+                Some((class, length)) => {
+                    index += length;
+                    class
+                }
+                None => Token::Char('['),
+            },
+            (c, _) => Token::Char(c),
+        };
+        tokens.push(token);
+        index += 1;
+    }
+    tokens
+}
+
+/// Parse a character class after the opening `[`.
+///
+/// Returns the class and the number of consumed characters including the closing `]`.
+fn parse_class(chars: &[(char, bool)]) -> Option<(Token, usize)> {
+    let negated = matches!(chars.first(), Some(('!' | '^', false)));
+    let start = usize::from(negated);
+    // A ']' directly after the opening bracket is part of the class.
+    let end = start
+        + 1
+        + chars
+            .get(start + 1..)?
+            .iter()
+            .position(|&c| c == (']', false))?;
+    let members = &chars[start..end];
+
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < members.len() {
+        let (first, _) = members[index];
+        match members.get(index + 1..index + 3) {
+            Some([('-', false), (last, _)]) => {
+                ranges.push((first, *last));
+                index += 3;
+            }
+            _ => {
+                ranges.push((first, first));
+                index += 1;
+            }
+        }
+    }
+    Some((Token::Class { negated, ranges }, end + 1))
+}
+
+fn match_tokens(tokens: &[Token], name: &[char]) -> bool {
+    match tokens.split_first() {
+        None => name.is_empty(),
+        Some((Token::Any, rest)) => (0..=name.len()).any(|skip| match_tokens(rest, &name[skip..])),
+        Some((token, rest)) => match name.split_first() {
+            Some((c, name_rest)) => match_char(token, *c) && match_tokens(rest, name_rest),
+            None => false,
+        },
+    }
+}
+
+fn match_char(token: &Token, c: char) -> bool {
+    match token {
+        Token::Char(expected) => *expected == c,
+        Token::One => true,
+        Token::Class { negated, ranges } => {
+            ranges
+                .iter()
+                .any(|(first, last)| (*first..=*last).contains(&c))
+                != *negated
+        }
+        Token::Any => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        Segment::new(&lex(pattern)).matches(name)
+    }
+
+    #[test]
+    fn match_literal() {
+        assert!(matches("data", "data"));
+        assert!(!matches("data", "data1"));
+    }
+
+    #[test]
+    fn match_star() {
+        assert!(matches("det*", "detector_1"));
+        assert!(matches("*_1", "detector_1"));
+        assert!(matches("*", ""));
+        assert!(!matches("det*", "monitor"));
+    }
+
+    #[test]
+    fn match_question_mark() {
+        assert!(matches("detector_?", "detector_1"));
+        assert!(!matches("detector_?", "detector_12"));
+    }
+
+    #[test]
+    fn match_class() {
+        assert!(matches("detector_[12]", "detector_2"));
+        assert!(!matches("detector_[12]", "detector_3"));
+        assert!(matches("detector_[0-9]", "detector_7"));
+        assert!(matches("detector_[!0-9]", "detector_x"));
+        assert!(!matches("detector_[^0-9]", "detector_7"));
+    }
+
+    #[test]
+    fn match_unclosed_bracket_is_literal() {
+        assert!(matches("a[b*", "a[bc"));
+    }
+
+    #[test]
+    fn quoted_characters_are_literal() {
+        assert!(matches("'det*'", "det*"));
+        assert!(!matches("'det*'", "detector"));
+        assert!(matches("det\\?", "det?"));
+        assert!(!is_pattern("\"*\""));
+    }
+
+    #[test]
+    fn is_pattern_detects_globs() {
+        assert!(is_pattern("entry/*/data"));
+        assert!(is_pattern("**/data"));
+        assert!(is_pattern("detector_[12]"));
+        assert!(!is_pattern("entry/data"));
+        assert!(!is_pattern("../entry"));
+        assert!(!is_pattern("a[b"));
+    }
+
+    #[test]
+    fn unquote_removes_quotes_and_escapes() {
+        assert_eq!(unquote("'a b'"), "a b");
+        assert_eq!(unquote("\"a'b\""), "a'b");
+        assert_eq!(unquote("a\\*b"), "a*b");
+    }
+
+    #[test]
+    fn unquote_escapes_in_double_quotes() {
+        assert_eq!(unquote(r#""a\"b""#), "a\"b");
+        assert_eq!(unquote(r"'a\b'"), r"a\b");
+    }
+
+    #[test]
+    fn escape_is_inverse_of_unquote() {
+        let name = "a b'c\"d\\e$f*g?h[i]@j";
+        assert_eq!(unquote(&escape(name)), name);
+        assert!(!is_pattern(&escape(name)));
+        assert_eq!(escape("data set"), r"data\ set");
+    }
+
+    #[test]
+    fn unclosed_quote_at_end() {
+        assert_eq!(unclosed_quote("'a b"), Some('\''));
+        assert_eq!(unclosed_quote(r#""a\""#), Some('"'));
+        assert_eq!(unclosed_quote("'a b'"), None);
+        assert_eq!(unclosed_quote(r"a\'b"), None);
+    }
+
+    #[test]
+    fn split_attr_at_unquoted_at() {
+        assert_eq!(split_attr("det_?@units"), ("det_?", Some("units")));
+        assert_eq!(split_attr("'a@b'@c"), ("'a@b'", Some("c")));
+        assert_eq!(split_attr("a\\@b"), ("a\\@b", None));
+    }
+
+    fn build_cache() -> FileCache<i32> {
+        let mut cache = FileCache::new();
+        cache.insert_group(&H5Path::root(), 0);
+        cache
+    }
+
+    fn load_children(value: &i32) -> Result<Vec<(H5Path, i32, bool)>> {
+        let children: &[(&str, i32, bool)] = match value {
+            0 => &[("/entry", 1, true), ("/notes", 2, false)],
+            1 => &[
+                ("/entry/detector_1", 3, true),
+                ("/entry/detector_2", 4, true),
+                ("/entry/monitor", 5, true),
+                ("/entry/title", 6, false),
+            ],
+            3 => &[("/entry/detector_1/data", 7, false)],
+            4 => &[("/entry/detector_2/data", 8, false)],
+            5 => &[("/entry/monitor/data", 9, false)],
+            _ => &[],
+        };
+        Ok(children
+            .iter()
+            .map(|(path, value, is_group)| (H5Path::from(*path), *value, *is_group))
+            .collect())
+    }
+
+    fn expand_str(pattern: &str, working_group: &str) -> Result<Vec<String>> {
+        let mut cache = build_cache();
+        expand(
+            pattern,
+            &H5Path::from(working_group),
+            &mut cache,
+            load_children,
+            |value: &i32| Some(*value),
+        )
+        .map(|paths| paths.iter().map(|path| path.as_raw().to_string()).collect())
+    }
+
+    #[test]
+    fn expand_star_in_middle() {
+        assert_eq!(
+            expand_str("entry/*/data", "/").unwrap(),
+            vec![
+                "entry/detector_1/data",
+                "entry/detector_2/data",
+                "entry/monitor/data"
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_absolute() {
+        assert_eq!(
+            expand_str("/entry/detector_?", "/entry/monitor").unwrap(),
+            vec!["/entry/detector_1", "/entry/detector_2"]
+        );
+    }
+
+    #[test]
+    fn expand_relative_to_working_group() {
+        assert_eq!(
+            expand_str("detector_[2-9]", "/entry").unwrap(),
+            vec!["detector_2"]
+        );
+        assert_eq!(
+            expand_str("../detector_1/*", "/entry/monitor").unwrap(),
+            vec!["../detector_1/data"]
+        );
+    }
+
+    #[test]
+    fn expand_non_final_segments_only_match_groups() {
+        assert_eq!(
+            expand_str("/entry/t*/data", "/").unwrap_err().to_string(),
+            H5Error::Other("No matches for pattern: /entry/t*/data".to_string()).to_string()
+        );
+    }
+
+    #[test]
+    fn expand_recursive() {
+        assert_eq!(
+            expand_str("**/data", "/").unwrap(),
+            vec![
+                "entry/detector_1/data",
+                "entry/detector_2/data",
+                "entry/monitor/data"
+            ]
+        );
+        assert_eq!(
+            expand_str("/entry/**", "/").unwrap(),
+            vec![
+                "/entry/detector_1",
+                "/entry/detector_1/data",
+                "/entry/detector_2",
+                "/entry/detector_2/data",
+                "/entry/monitor",
+                "/entry/monitor/data",
+                "/entry/title"
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_no_match() {
+        assert!(expand_str("/entry/sample*", "/").is_err());
+    }
+
+    /// A file where `/a/loop` is a hard link to `/a` and `/a/up` is a soft link to `/`.
+    fn load_cyclic_children(value: &i32) -> Result<Vec<(H5Path, i32, bool)>> {
+        let children: &[(&str, i32, bool)] = match value {
+            0 | 10 => &[("/a", 1, true)],
+            1 => &[
+                ("/a/data", 2, false),
+                ("/a/loop", 1, true),
+                ("/a/up", 10, true),
+            ],
+            _ => &[],
+        };
+        Ok(children
+            .iter()
+            .map(|(path, value, is_group)| (H5Path::from(*path), *value, *is_group))
+            .collect())
+    }
+
+    #[test]
+    fn expand_recursive_skips_cycles_and_soft_links() {
+        let mut cache = build_cache();
+        let expanded = expand(
+            "**",
+            &H5Path::root(),
+            &mut cache,
+            load_cyclic_children,
+            // Values from 10 are soft links.
+            |value: &i32| (*value < 10).then_some(*value),
+        )
+        .unwrap();
+        assert_eq!(
+            expanded.iter().map(H5Path::as_raw).collect::<Vec<_>>(),
+            vec!["a", "a/data", "a/loop", "a/up"]
+        );
+    }
+}
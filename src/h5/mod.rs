@@ -1,13 +1,17 @@
 pub mod cache;
 mod error;
 mod file;
+mod file_info;
+pub mod glob;
 mod object;
 mod object_path;
 mod path;
+pub mod traverse;
 
-pub use cache::{CacheEntry, CacheEntryId, CacheValue, FileCache, H5FileCache};
+pub use cache::{CacheEntry, CacheEntryId, Existence, FileCache, H5FileCache};
 pub use error::{H5Error, Result};
-pub use file::H5File;
+pub use file::{Driver, H5File, LibraryVersion, OpenOptions};
+pub use file_info::{FileInfo, FileStatus, OpenObjects};
 pub use object::*;
 pub use object_path::ObjectPath;
 pub use path::H5Path;
@@ -1,7 +1,7 @@
 use crate::h5::{H5Error, H5Path, Result};
-use hdf5::{LocationInfo, LocationType};
+use hdf5::{Hyperslab, LinkType, LocationInfo, LocationType, SliceOrIndex};
 use ndarray::{Array, IxDyn};
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
 #[derive(Clone, Debug)]
 pub struct H5Dataset {
@@ -60,6 +60,28 @@ impl H5Dataset {
     pub fn attr(&self, name: &str) -> Result<H5Attribute> {
         Ok(H5Attribute::from_underlying(self.underlying().attr(name)?))
     }
+
+    /// Read a range of indices along the first axis.
+    pub fn read_rows<T: hdf5::H5Type>(&self, rows: Range<usize>) -> Result<Array<T, IxDyn>> {
+        let mut selection = vec![SliceOrIndex::from(rows)];
+        selection.resize(self.underlying().ndim(), SliceOrIndex::from(..));
+        Ok(self
+            .underlying()
+            .read_slice::<T, _, IxDyn>(Hyperslab::from(selection))?)
+    }
+
+    /// Re-read the metadata, e.g., the shape, to see changes made by other programs.
+    pub fn refresh(&self) -> Result<()> {
+        let status =
+            hdf5::sync::sync(|| unsafe { hdf5_sys::h5d::H5Drefresh(self.underlying().id()) });
+        if status < 0 {
+            return Err(H5Error::Other(format!(
+                "Failed to refresh dataset {}",
+                self.path
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl H5Group {
@@ -91,37 +113,42 @@ impl H5Group {
     }
 
     pub fn load_children(&self) -> Result<Vec<H5Object>> {
-        fn load_child(parent: &hdf5::Group, name: &str) -> Result<H5Object> {
-            match parent.loc_type_by_name(name)? {
-                LocationType::Dataset => Ok(H5Dataset::from_underlying_with_path(
-                    parent.dataset(name)?,
-                    name.into(),
-                )
-                .into()),
-                LocationType::Group => {
-                    Ok(H5Group::from_underlying_with_path(parent.group(name)?, name.into()).into())
-                }
-                _ => Err(H5Error::Other("unsupported location type: ".into())),
-            }
-        }
+        Ok(self
+            .load_linked_children()?
+            .into_iter()
+            .map(|(child, _)| child)
+            .collect())
+    }
 
+    /// Load the children together with the type of the link that points to them.
+    pub fn load_linked_children(&self) -> Result<Vec<(H5Object, LinkType)>> {
         Ok(self
             .underlying()
-            .iter_visit_default(Vec::new(), |group, name, _, children| {
-                if let Ok(child) = load_child(group, name) {
-                    children.push(child);
+            .iter_visit_default(Vec::new(), |group, name, link, children| {
+                if let Ok(child) = load_child(group, name, name.into()) {
+                    children.push((child, link.link_type));
                 }
                 // Skip errors. We have no good way of reporting errors here.
                 true
             })?)
     }
 
-    pub fn load_child_locations(&self) -> Result<Vec<(H5Path, LocationInfo)>> {
+    /// Load an object by its path relative to this group.
+    pub fn load_descendant(&self, relative_path: &str) -> Result<H5Object> {
+        load_child(
+            self.underlying(),
+            relative_path,
+            self.path.join(&H5Path::from(relative_path)),
+        )
+    }
+
+    /// Return the paths and locations of all children and the types of the links to them.
+    pub fn load_child_locations(&self) -> Result<Vec<(H5Path, LocationInfo, LinkType)>> {
         Ok(self
             .underlying()
-            .iter_visit_default(Vec::new(), |group, name, _, children| {
+            .iter_visit_default(Vec::new(), |group, name, link, children| {
                 if let Ok(info) = group.loc_info_by_name(name) {
-                    children.push((self.path.join(&H5Path::from(name)), info));
+                    children.push((self.path.join(&H5Path::from(name)), info, link.link_type));
                 }
                 // Skip errors. We have no good way of reporting errors here.
                 true
@@ -129,6 +156,18 @@ impl H5Group {
     }
 }
 
+fn load_child(parent: &hdf5::Group, name: &str, path: H5Path) -> Result<H5Object> {
+    match parent.loc_type_by_name(name)? {
+        LocationType::Dataset => {
+            Ok(H5Dataset::from_underlying_with_path(parent.dataset(name)?, path).into())
+        }
+        LocationType::Group => {
+            Ok(H5Group::from_underlying_with_path(parent.group(name)?, path).into())
+        }
+        _ => Err(H5Error::Other("unsupported location type: ".into())),
+    }
+}
+
 impl H5Attribute {
     pub fn from_underlying(underlying: hdf5::Attribute) -> Self {
         Self {
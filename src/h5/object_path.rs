@@ -8,32 +8,56 @@ use std::fmt::Display;
 ///
 /// Object paths are encoded as
 /// `location_path@attr_path`.
+/// A `@` or `\` in the location path must be escaped with a backslash.
 #[derive(Clone, Debug)]
 pub struct ObjectPath {
     pub location_path: H5Path,
     pub attr_name: Option<String>,
 }
 
+impl ObjectPath {
+    /// Escape a location path such that it can be encoded in an object path.
+    pub fn escape_location(location: &str) -> String {
+        let mut escaped = String::with_capacity(location.len());
+        for c in location.chars() {
+            if matches!(c, '@' | '\\') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
 impl From<String> for ObjectPath {
     fn from(value: String) -> Self {
-        match value.split_once('@') {
-            Some((a, b)) => Self {
-                location_path: a.into(),
-                attr_name: Some(b.into()),
-            },
-            None => Self {
-                location_path: value.into(),
-                attr_name: None,
-            },
+        let mut location = String::with_capacity(value.len());
+        let mut chars = value.char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '\\' => location.extend(chars.next().map(|(_, escaped)| escaped)),
+                '@' => {
+                    return Self {
+                        location_path: location.into(),
+                        attr_name: Some(value[index + 1..].into()),
+                    };
+                }
+                _ => location.push(c),
+            }
+        }
+        Self {
+            location_path: location.into(),
+            attr_name: None,
         }
     }
 }
 
 impl Display for ObjectPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let location = Self::escape_location(self.location_path.as_raw());
         match &self.attr_name {
-            Some(attr) => write!(f, "{}@{}", self.location_path, attr),
-            None => write!(f, "{}", self.location_path),
+            Some(attr) => write!(f, "{location}@{attr}"),
+            None => write!(f, "{location}"),
         }
     }
 }
@@ -82,6 +106,20 @@ mod tests {
         assert_eq!(path.attr_name, Some("an_attribute".to_string()));
     }
 
+    #[test]
+    fn object_path_from_string_escaped_at() {
+        let raw = r"data\@1\\x@units@2".to_string();
+        let path = ObjectPath::from(raw);
+        assert_eq!(path.location_path, H5Path::from(r"data@1\x"));
+        assert_eq!(path.attr_name, Some("units@2".to_string()));
+    }
+
+    #[test]
+    fn object_path_display_round_trip() {
+        let raw = r"a\@b@c".to_string();
+        assert_eq!(ObjectPath::from(raw.clone()).to_string(), raw);
+    }
+
     #[test]
     fn object_path_from_string_full_trailing_slash() {
         let raw = "long/base/@an_attribute".to_string();
@@ -0,0 +1,473 @@
+//! Recursive traversal of groups.
+//!
+//! The traversal is driven by `H5Lvisit` which walks the entire hierarchy below a group
+//! in a single library call instead of opening and iterating every group separately.
+//! [`walk`] builds on it to report every object once, even if it can be reached
+//! through multiple links or through cycles of links.
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::ops::ControlFlow;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use hdf5::LinkType;
+use hdf5_sys::h5::{H5_index_t, H5_iter_order_t, herr_t};
+use hdf5_sys::h5i::hid_t;
+use hdf5_sys::h5l::{H5L_info2_t, H5L_type_t, H5Lvisit2};
+use hdf5_sys::h5o::{
+    H5O_INFO_BASIC, H5O_MAX_TOKEN_SIZE, H5O_info2_t, H5O_token_t, H5O_type_t, H5Oget_info_by_name3,
+};
+use hdf5_sys::h5p::H5P_DEFAULT;
+
+use super::{H5Error, H5Group, H5Object, Result};
+
+/// Identifies an object in a file.
+///
+/// This is the same as `hdf5::LocationToken` but can be used as a key in a hash map.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Token([u8; H5O_MAX_TOKEN_SIZE]);
+
+impl From<H5O_token_t> for Token {
+    fn from(token: H5O_token_t) -> Self {
+        // SAFETY: `H5O_token_t` is a `repr(C)` struct that only contains an array of this size.
+        Self(unsafe { std::mem::transmute::<H5O_token_t, [u8; H5O_MAX_TOKEN_SIZE]>(token) })
+    }
+}
+
+/// A link encountered during traversal.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry<'a> {
+    /// Path of the link relative to the group where the traversal started.
+    pub path: &'a str,
+    /// Number of groups between the start and the link, children have depth 1.
+    pub depth: usize,
+    pub link_type: LinkType,
+    /// The object that a hard link points to, `None` for soft and external links.
+    pub token: Option<Token>,
+}
+
+/// Call `visitor` for every link below `group` in name order.
+///
+/// Parents are visited before their children.
+/// Groups are entered only once even if there are multiple hard links to them,
+/// and soft and external links are reported but not followed.
+/// Links deeper than `max_depth` are skipped but HDF5 still has to walk over them.
+///
+/// The traversal stops when `visitor` returns [`ControlFlow::Break`],
+/// and the break value is returned.
+pub fn visit<B>(
+    group: &H5Group,
+    max_depth: Option<usize>,
+    mut visitor: impl FnMut(&Entry) -> ControlFlow<B>,
+) -> Result<ControlFlow<B>> {
+    let mut state = State {
+        visitor: &mut visitor,
+        max_depth,
+        result: None,
+    };
+    let id = group.underlying().id();
+    let status = hdf5::sync::sync(|| unsafe {
+        H5Lvisit2(
+            id,
+            H5_index_t::H5_INDEX_NAME,
+            H5_iter_order_t::H5_ITER_INC,
+            Some(callback::<B>),
+            (&mut state as *mut State<B>).cast::<c_void>(),
+        )
+    });
+    if let Some(value) = state.result {
+        return Ok(ControlFlow::Break(value));
+    }
+    if status < 0 {
+        return Err(H5Error::Other(format!(
+            "Failed to traverse '{}'",
+            group.path()
+        )));
+    }
+    Ok(ControlFlow::Continue(()))
+}
+
+/// Options for [`walk`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WalkOptions {
+    /// Skip objects more than this many levels below the start.
+    pub max_depth: Option<usize>,
+    /// Treat soft links like hard links and descend into the groups they point to.
+    /// External links are never followed.
+    pub follow_links: bool,
+}
+
+/// An object found by [`walk`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Node {
+    /// Path relative to the start of the first link to the object.
+    pub path: String,
+    pub depth: usize,
+    pub link_type: LinkType,
+}
+
+impl Node {
+    /// Split the path into the path of the parent group and the name of the link.
+    ///
+    /// The parent is empty for children of the start group.
+    pub fn split(&self) -> (&str, &str) {
+        self.path.rsplit_once('/').unwrap_or(("", &self.path))
+    }
+}
+
+/// A link reported by [`walk`].
+#[derive(Clone, Copy, Debug)]
+pub enum Visit<'a> {
+    /// The first link to an object.
+    Object(&'a Node),
+    /// Another link to an object that was reported before.
+    Alias {
+        /// Path of the link relative to the start.
+        path: &'a str,
+        /// The node that was reported for the object.
+        node: &'a Node,
+    },
+    /// A link back to the start group.
+    ///
+    /// Objects below it are only reported as aliases.
+    Cycle(&'a Node),
+}
+
+/// A node and whether a hard link to it has been seen.
+struct Record {
+    node: Node,
+    /// `H5Lvisit` enters groups behind hard links by itself.
+    entered: bool,
+}
+
+/// Find all objects below `group` and report each object only once.
+///
+/// `on_visit` is called for every object as soon as it is found,
+/// and other links to objects that were already reported are passed as aliases.
+/// Links to the start group are reported as cycles.
+/// Soft links are reported as separate objects unless they are followed.
+/// Groups are only entered once which makes this safe for cyclic hierarchies.
+///
+/// The traversal stops when `on_visit` returns [`ControlFlow::Break`],
+/// and the break value is returned.
+pub fn walk<B>(
+    group: &H5Group,
+    options: WalkOptions,
+    mut on_visit: impl FnMut(Visit) -> ControlFlow<B>,
+) -> Result<ControlFlow<B>> {
+    let mut records: Vec<Record> = Vec::new();
+    // Maps tokens to indices in `records`, `None` for the start group.
+    let mut seen: HashMap<Token, Option<usize>> = HashMap::new();
+    if let Some((token, _)) = object_info(group, ".") {
+        seen.insert(token, None);
+    }
+
+    // Groups to traverse as indices into `records`, `None` for the start group.
+    // Groups behind followed soft links are traversed after the start group.
+    let mut pending = VecDeque::from([None]);
+    while let Some(index) = pending.pop_front() {
+        let (start, prefix, offset) = match index {
+            None => (group.clone(), String::new(), 0),
+            Some(index) => {
+                let record: &Record = &records[index];
+                if record.entered {
+                    continue;
+                }
+                let node = &record.node;
+                let Ok(H5Object::Group(start)) = group.load_descendant(&node.path) else {
+                    continue;
+                };
+                (start, node.path.clone(), node.depth)
+            }
+        };
+        let max_depth = options.max_depth.map(|max| max - offset);
+        let flow = visit(&start, max_depth, |entry| {
+            let path = if prefix.is_empty() {
+                entry.path.to_string()
+            } else {
+                format!("{prefix}/{}", entry.path)
+            };
+            let depth = offset + entry.depth;
+            let is_hard = entry.link_type == LinkType::Hard;
+
+            let follow = options.follow_links && entry.link_type == LinkType::Soft;
+            let (token, is_group) = if follow {
+                match object_info(group, &path) {
+                    Some((token, is_group)) => (Some(token), is_group),
+                    None => (None, false),
+                }
+            } else {
+                (entry.token, false)
+            };
+
+            if let Some(token) = token {
+                if let Some(index) = seen.get(&token) {
+                    return match index.map(|index| &mut records[index]) {
+                        Some(record) => {
+                            record.entered |= is_hard;
+                            on_visit(Visit::Alias {
+                                path: &path,
+                                node: &record.node,
+                            })
+                        }
+                        None => on_visit(Visit::Cycle(&Node {
+                            path,
+                            depth,
+                            link_type: entry.link_type,
+                        })),
+                    };
+                }
+                seen.insert(token, Some(records.len()));
+            }
+            if is_group && options.max_depth.is_none_or(|max| depth < max) {
+                pending.push_back(Some(records.len()));
+            }
+            records.push(Record {
+                node: Node {
+                    path,
+                    depth,
+                    link_type: entry.link_type,
+                },
+                entered: is_hard,
+            });
+            let node = &records[records.len() - 1].node;
+            on_visit(Visit::Object(node))
+        })?;
+        if let ControlFlow::Break(value) = flow {
+            return Ok(ControlFlow::Break(value));
+        }
+    }
+    Ok(ControlFlow::Continue(()))
+}
+
+/// Return the token of the object at `path` and whether it is a group.
+///
+/// Follows soft links and returns `None` if the object does not exist.
+fn object_info(group: &H5Group, path: &str) -> Option<(Token, bool)> {
+    let name = CString::new(path).ok()?;
+    // SAFETY: All fields of the struct are plain numbers.
+    let mut info: H5O_info2_t = unsafe { std::mem::zeroed() };
+    let status = hdf5::sync::sync(|| unsafe {
+        H5Oget_info_by_name3(
+            group.underlying().id(),
+            name.as_ptr(),
+            &mut info,
+            H5O_INFO_BASIC,
+            H5P_DEFAULT,
+        )
+    });
+    (status >= 0).then(|| (info.token.into(), info.type_ == H5O_type_t::H5O_TYPE_GROUP))
+}
+
+struct State<'v, B> {
+    visitor: &'v mut dyn FnMut(&Entry) -> ControlFlow<B>,
+    max_depth: Option<usize>,
+    result: Option<B>,
+}
+
+/// Returns 0 to continue, 1 to stop, and -1 on failure as required by `H5Lvisit`.
+unsafe extern "C" fn callback<B>(
+    _group: hid_t,
+    name: *const c_char,
+    info: *const H5L_info2_t,
+    op_data: *mut c_void,
+) -> herr_t {
+    catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: HDF5 passes valid pointers and `op_data` is the state created in `visit`.
+        let (state, name, info) = unsafe {
+            (
+                &mut *op_data.cast::<State<B>>(),
+                CStr::from_ptr(name).to_string_lossy(),
+                &*info,
+            )
+        };
+        let depth = name.matches('/').count() + 1;
+        if state.max_depth.is_some_and(|max| depth > max) {
+            return 0;
+        }
+        let token = match info.type_ {
+            // SAFETY: The union holds a token for hard links.
+            H5L_type_t::H5L_TYPE_HARD => Some(unsafe { *{ info.u }.token() }.into()),
+            _ => None,
+        };
+        let entry = Entry {
+            path: &name,
+            depth,
+            link_type: info.type_.into(),
+            token,
+        };
+        match (state.visitor)(&entry) {
+            ControlFlow::Continue(()) => 0,
+            ControlFlow::Break(value) => {
+                state.result = Some(value);
+                1
+            }
+        }
+    }))
+    .unwrap_or(-1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Create a file with multiple links to the same objects and cycles.
+    fn make_file(name: &str) -> (std::path::PathBuf, H5Group) {
+        let path =
+            std::env::temp_dir().join(format!("h5sh-traverse-{name}-{}.h5", std::process::id()));
+        let file = hdf5::File::create(&path).unwrap();
+        let a = file.create_group("a").unwrap();
+        a.new_dataset::<f64>().shape([2]).create("data").unwrap();
+        a.link_soft("/", "up").unwrap();
+        file.link_hard("/a/data", "b").unwrap();
+        let c = file.create_group("c").unwrap();
+        c.new_dataset::<i32>().shape([1]).create("d").unwrap();
+        c.link_hard("/c", "loop").unwrap();
+        file.link_soft("/missing", "dangling").unwrap();
+        file.link_soft("/a", "link").unwrap();
+        let root = H5Group::from_underlying_with_path(file.group("/").unwrap(), "/".into());
+        (path, root)
+    }
+
+    /// Return the nodes and their aliases in the order in which they were reported.
+    ///
+    /// Cycles are listed as aliases of the start group, `"."`.
+    fn walk_paths(group: &H5Group, options: WalkOptions) -> Vec<(String, Vec<String>)> {
+        let mut nodes: Vec<(String, Vec<String>)> = vec![(".".to_string(), Vec::new())];
+        let flow = walk::<()>(group, options, |visit| {
+            match visit {
+                Visit::Object(node) => nodes.push((node.path.clone(), Vec::new())),
+                Visit::Alias { path, node } => {
+                    if let Some((_, aliases)) = nodes.iter_mut().find(|(p, _)| *p == node.path) {
+                        aliases.push(path.to_string());
+                    }
+                }
+                Visit::Cycle(node) => nodes[0].1.push(node.path.clone()),
+            }
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(flow.is_continue(), "traversal was stopped");
+        if nodes[0].1.is_empty() {
+            nodes.remove(0);
+        }
+        nodes
+    }
+
+    fn node(path: &str, aliases: &[&str]) -> (String, Vec<String>) {
+        (
+            path.to_string(),
+            aliases.iter().map(|alias| alias.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn walk_merges_hard_links() {
+        let (path, root) = make_file("hard");
+        let nodes = walk_paths(&root, WalkOptions::default());
+        let _ = std::fs::remove_file(path);
+        assert_eq!(
+            nodes,
+            vec![
+                node("a", &[]),
+                node("a/data", &["b"]),
+                node("a/up", &[]),
+                node("c", &["c/loop"]),
+                node("c/d", &[]),
+                node("dangling", &[]),
+                node("link", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_follows_soft_links() {
+        let (path, root) = make_file("soft");
+        let options = WalkOptions {
+            max_depth: None,
+            follow_links: true,
+        };
+        let nodes = walk_paths(&root, options);
+        let _ = std::fs::remove_file(path);
+        assert_eq!(
+            nodes,
+            vec![
+                node(".", &["a/up"]),
+                node("a", &["link"]),
+                node("a/data", &["b"]),
+                node("c", &["c/loop"]),
+                node("c/d", &[]),
+                node("dangling", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_stops_at_max_depth() {
+        let (path, root) = make_file("depth");
+        let options = WalkOptions {
+            max_depth: Some(1),
+            follow_links: true,
+        };
+        let nodes = walk_paths(&root, options);
+        let _ = std::fs::remove_file(path);
+        assert_eq!(
+            nodes,
+            vec![
+                node("a", &["link"]),
+                node("b", &[]),
+                node("c", &[]),
+                node("dangling", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_reports_objects_before_the_traversal_ends() {
+        let (path, root) = make_file("stop");
+        let mut visited = Vec::new();
+        let flow = walk(&root, WalkOptions::default(), |visit| match visit {
+            Visit::Object(node) => {
+                visited.push(node.path.clone());
+                ControlFlow::Break(())
+            }
+            Visit::Alias { .. } | Visit::Cycle(_) => ControlFlow::Continue(()),
+        })
+        .unwrap();
+        let _ = std::fs::remove_file(path);
+        assert!(flow.is_break());
+        assert_eq!(visited, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn walk_reports_links_to_the_start_as_cycles() {
+        let (path, root) = make_file("cycle");
+        let Ok(H5Object::Group(c)) = root.load_descendant("c") else {
+            panic!("c is not a group");
+        };
+        let Ok(H5Object::Group(a)) = root.load_descendant("a") else {
+            panic!("a is not a group");
+        };
+        let options = WalkOptions {
+            max_depth: None,
+            follow_links: true,
+        };
+        let hard = walk_paths(&c, options);
+        let soft = walk_paths(&a, options);
+        let _ = std::fs::remove_file(path);
+        assert_eq!(hard, vec![node(".", &["loop"]), node("d", &[])]);
+        // 'up' leads to the root and from there back to 'a'.
+        assert_eq!(
+            soft,
+            vec![
+                node(".", &["up/a", "up/link"]),
+                node("data", &["up/a/data", "up/b"]),
+                node("up", &["up/a/up"]),
+                node("up/c", &["up/c/loop"]),
+                node("up/c/d", &[]),
+                node("up/dangling", &[]),
+            ]
+        );
+    }
+}
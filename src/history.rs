@@ -0,0 +1,530 @@
+use anyhow::{Context, Result};
+use log::info;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Dedup, HistoryConfig};
+
+/// Commands that were run, tagged with the file they were run against.
+///
+/// The history of all files is stored in a single file with one entry per line
+/// and the file name and command separated by a tab.
+/// Lines without a tab come from older versions and have an unknown file.
+pub struct History {
+    entries: Vec<Entry>,
+    /// Entries that were added in this session.
+    new_entries: Vec<Entry>,
+    /// The file that new entries are run against.
+    file: String,
+    path: PathBuf,
+    /// History file of older versions that is imported if there is no history file yet.
+    /// It is never written.
+    legacy_path: Option<PathBuf>,
+    size: usize,
+    dedup: Dedup,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// Empty if the file is unknown.
+    pub file: String,
+    pub command: String,
+}
+
+impl Entry {
+    /// Return true if the command was run against `file` or the file is unknown.
+    pub fn is_of_file(&self, file: &str) -> bool {
+        self.file.is_empty() || self.file == file
+    }
+}
+
+impl History {
+    pub fn new(config: &HistoryConfig, file: String) -> Self {
+        Self {
+            entries: Vec::new(),
+            new_entries: Vec::new(),
+            file,
+            path: config.path(),
+            legacy_path: config.legacy_path(),
+            size: config.size,
+            dedup: config.dedup,
+        }
+    }
+
+    /// Load the history file from the configured location.
+    pub fn load(config: &HistoryConfig, file: String) -> Self {
+        let mut history = Self::new(config, file);
+        match history.read_entries() {
+            Ok(entries) => history.entries = entries,
+            Err(err) => info!("No history loaded: {err:#}"),
+        }
+        history
+    }
+
+    /// Read the entries from the history file or from the legacy file if there is none.
+    ///
+    /// Missing files result in an empty history, all other errors are returned.
+    fn read_entries(&self) -> Result<Vec<Entry>> {
+        if let Some(content) = read_file(&self.path)? {
+            return Ok(parse(&content));
+        }
+        if let Some(legacy_path) = &self.legacy_path
+            && let Some(content) = read_file(legacy_path)?
+        {
+            info!("Importing history from {}", legacy_path.display());
+            return Ok(parse(&content));
+        }
+        info!("No previous history at {}", self.path.display());
+        Ok(Vec::new())
+    }
+
+    /// Write the history to the history file.
+    ///
+    /// Entries that were written by other sessions in the meantime are preserved.
+    /// The file is not written if it exists but cannot be read.
+    pub fn save(&self) -> Result<()> {
+        let mut entries = self.read_entries()?;
+        for entry in &self.new_entries {
+            push_entry(&mut entries, entry.clone(), self.dedup);
+        }
+        let start = entries.len().saturating_sub(self.size);
+
+        if let Some(parent) = self.path.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, format_entries(&entries[start..]))?;
+        Ok(())
+    }
+
+    /// The file that new entries are run against.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Add a command that was run against the current file.
+    ///
+    /// Commands that start with a space are not added.
+    pub fn add(&mut self, command: &str) {
+        if command.trim().is_empty() || command.starts_with(char::is_whitespace) {
+            return;
+        }
+        let entry = Entry {
+            file: self.file.clone(),
+            command: command.to_string(),
+        };
+        self.new_entries.push(entry.clone());
+        push_entry(&mut self.entries, entry, self.dedup);
+    }
+
+    /// Return all entries with their numbers, oldest first.
+    ///
+    /// Numbers start at 1 and refer to the history of all files.
+    pub fn numbered(&self) -> impl DoubleEndedIterator<Item = (usize, &Entry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (index + 1, entry))
+    }
+
+    /// Return the commands that were run against the current file, oldest first.
+    ///
+    /// This includes commands with an unknown file.
+    pub fn commands_of_file(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_of_file(&self.file))
+            .map(|entry| entry.command.as_str())
+    }
+
+    /// Return the commands of all files, oldest first.
+    pub fn commands(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.command.as_str())
+    }
+
+    /// Replace history references in a line.
+    ///
+    /// - `!!` is the previous command,
+    /// - `!N` is the command with number N,
+    /// - `!-N` is the N-th previous command,
+    /// - `!prefix` is the most recent command that starts with `prefix`.
+    ///
+    /// Only commands of the current file are used except for `!N`.
+    /// No expansion happens in single quotes, after a backslash,
+    /// or if `!` is followed by a space or `=`.
+    pub fn expand(&self, line: &str) -> Result<String, String> {
+        let mut expanded = String::with_capacity(line.len());
+        let mut rest = line;
+        let mut in_single_quotes = false;
+        let mut in_double_quotes = false;
+        while let Some(c) = rest.chars().next() {
+            let after = &rest[c.len_utf8()..];
+            match c {
+                '\'' if !in_double_quotes => in_single_quotes = !in_single_quotes,
+                '"' if !in_single_quotes => in_double_quotes = !in_double_quotes,
+                '\\' if !in_single_quotes => {
+                    expanded.push(c);
+                    if let Some(escaped) = after.chars().next() {
+                        expanded.push(escaped);
+                        rest = &after[escaped.len_utf8()..];
+                    } else {
+                        rest = after;
+                    }
+                    continue;
+                }
+                '!' if !in_single_quotes => {
+                    if let Some((event, length)) = parse_event(after) {
+                        expanded.push_str(self.resolve_event(&event)?);
+                        rest = &after[length..];
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            expanded.push(c);
+            rest = after;
+        }
+        Ok(expanded)
+    }
+
+    fn resolve_event(&self, event: &Event) -> Result<&str, String> {
+        let mut commands = self.commands_of_file().rev();
+        let found = match event {
+            Event::Number(number) => number
+                .checked_sub(1)
+                .and_then(|index| self.entries.get(index))
+                .map(|entry| entry.command.as_str()),
+            Event::Previous(n) => n.checked_sub(1).and_then(|n| commands.nth(n)),
+            Event::Prefix(prefix) => commands.find(|command| command.starts_with(prefix)),
+        };
+        found.ok_or_else(|| format!("Event not found: !{event}"))
+    }
+}
+
+enum Event<'a> {
+    /// Entry with the given number.
+    Number(usize),
+    /// N-th previous command.
+    Previous(usize),
+    /// Most recent command that starts with a prefix.
+    Prefix(&'a str),
+}
+
+impl std::fmt::Display for Event<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Number(number) => write!(f, "{number}"),
+            Event::Previous(1) => write!(f, "!"),
+            Event::Previous(n) => write!(f, "-{n}"),
+            Event::Prefix(prefix) => write!(f, "{prefix}"),
+        }
+    }
+}
+
+/// Parse the event after a `!` and return it with its length.
+fn parse_event(text: &str) -> Option<(Event<'_>, usize)> {
+    if text.starts_with('!') {
+        return Some((Event::Previous(1), 1));
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '\'' | '"' | '=' | '('))
+        .unwrap_or(text.len());
+    let word = &text[..end];
+    if word.is_empty() {
+        return None;
+    }
+    if let Some(n) = word.strip_prefix('-') {
+        return n.parse().ok().map(|n| (Event::Previous(n), end));
+    }
+    match word.parse() {
+        Ok(number) => Some((Event::Number(number), end)),
+        Err(_) => Some((Event::Prefix(word), end)),
+    }
+}
+
+/// Read a file and return `None` if it does not exist.
+fn read_file(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => {
+            Err(err).with_context(|| format!("Failed to read history file {}", path.display()))
+        }
+    }
+}
+
+fn push_entry(entries: &mut Vec<Entry>, entry: Entry, dedup: Dedup) {
+    match dedup {
+        Dedup::None => {}
+        Dedup::Consecutive => {
+            let previous = entries
+                .iter()
+                .rev()
+                .find(|existing| existing.file == entry.file);
+            if previous == Some(&entry) {
+                return;
+            }
+        }
+        Dedup::All => entries.retain(|existing| *existing != entry),
+    }
+    entries.push(entry);
+}
+
+fn parse(content: &str) -> Vec<Entry> {
+    content
+        .lines()
+        // Header of the format of older versions.
+        .filter(|line| *line != "#V2")
+        .map(|line| match line.split_once('\t') {
+            Some((file, command)) => Entry {
+                file: unescape(file),
+                command: unescape(command),
+            },
+            None => Entry {
+                file: String::new(),
+                command: unescape(line),
+            },
+        })
+        .collect()
+}
+
+fn format_entries(entries: &[Entry]) -> String {
+    let mut formatted = String::new();
+    for entry in entries {
+        formatted.push_str(&escape(&entry.file));
+        formatted.push('\t');
+        formatted.push_str(&escape(&entry.command));
+        formatted.push('\n');
+    }
+    formatted
+}
+
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn history(entries: &[(&str, &str)]) -> History {
+        let mut history = History::new(&HistoryConfig::default(), "a.h5".to_string());
+        history.entries = entries
+            .iter()
+            .map(|(file, command)| Entry {
+                file: file.to_string(),
+                command: command.to_string(),
+            })
+            .collect();
+        history
+    }
+
+    #[test]
+    fn add_ignores_consecutive_duplicates() {
+        let mut history = history(&[("a.h5", "ls")]);
+        history.add("ls");
+        history.add("cd a");
+        history.add("cd a");
+        assert_eq!(history.commands().collect::<Vec<_>>(), vec!["ls", "cd a"]);
+    }
+
+    #[test]
+    fn add_ignores_commands_starting_with_space() {
+        let mut history = history(&[]);
+        history.add(" ls");
+        history.add("  ");
+        assert_eq!(history.commands().count(), 0);
+    }
+
+    #[test]
+    fn add_keeps_duplicates_of_other_files() {
+        let mut history = history(&[("a.h5", "ls"), ("b.h5", "pwd")]);
+        history.add("ls");
+        assert_eq!(history.commands().collect::<Vec<_>>(), vec!["ls", "pwd"]);
+    }
+
+    #[test]
+    fn add_all_duplicates_removed() {
+        let mut history = history(&[]);
+        history.dedup = Dedup::All;
+        history.add("ls");
+        history.add("pwd");
+        history.add("ls");
+        assert_eq!(history.commands().collect::<Vec<_>>(), vec!["pwd", "ls"]);
+    }
+
+    #[test]
+    fn commands_of_file_filters_by_file() {
+        let history = history(&[("a.h5", "ls"), ("b.h5", "pwd"), ("a.h5", "cd x")]);
+        assert_eq!(
+            history.commands_of_file().collect::<Vec<_>>(),
+            vec!["ls", "cd x"]
+        );
+    }
+
+    #[test]
+    fn expand_previous() {
+        let history = history(&[("a.h5", "ls -l"), ("b.h5", "pwd")]);
+        assert_eq!(history.expand("!!").unwrap(), "ls -l");
+        assert_eq!(
+            history.expand("foreach a -- !!").unwrap(),
+            "foreach a -- ls -l"
+        );
+    }
+
+    #[test]
+    fn expand_number() {
+        let history = history(&[("a.h5", "ls -l"), ("b.h5", "pwd")]);
+        assert_eq!(history.expand("!2").unwrap(), "pwd");
+        assert_eq!(history.expand("!1 x").unwrap(), "ls -l x");
+        assert!(history.expand("!3").is_err());
+        assert!(history.expand("!0").is_err());
+    }
+
+    #[test]
+    fn expand_relative() {
+        let history = history(&[("a.h5", "ls"), ("a.h5", "cd x"), ("a.h5", "pwd")]);
+        assert_eq!(history.expand("!-1").unwrap(), "pwd");
+        assert_eq!(history.expand("!-3").unwrap(), "ls");
+        assert!(history.expand("!-4").is_err());
+    }
+
+    #[test]
+    fn expand_prefix() {
+        let history = history(&[("a.h5", "cat a"), ("a.h5", "cd x"), ("b.h5", "cat b")]);
+        assert_eq!(history.expand("!ca").unwrap(), "cat a");
+        assert_eq!(history.expand("!c").unwrap(), "cd x");
+        assert_eq!(
+            history.expand("!inspect").unwrap_err(),
+            "Event not found: !inspect"
+        );
+    }
+
+    #[test]
+    fn expand_ignores_quoted_and_lone_bang() {
+        let history = history(&[("a.h5", "ls")]);
+        assert_eq!(history.expand("find ! -name x").unwrap(), "find ! -name x");
+        assert_eq!(history.expand("set x=!").unwrap(), "set x=!");
+        assert_eq!(history.expand("cd 'a!!'").unwrap(), "cd 'a!!'");
+        assert_eq!(history.expand(r"cd a\!!").unwrap(), r"cd a\!!");
+        assert_eq!(history.expand(r#"cd "a'"!!"#).unwrap(), r#"cd "a'"ls"#);
+    }
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        let entries = vec![
+            Entry {
+                file: "/data/a.h5".to_string(),
+                command: "ls -l".to_string(),
+            },
+            Entry {
+                file: "/data/with\ttab.h5".to_string(),
+                command: r"cd a\ b".to_string(),
+            },
+        ];
+        assert_eq!(parse(&format_entries(&entries)), entries);
+    }
+
+    #[test]
+    fn parse_lines_without_file() {
+        assert_eq!(
+            parse("#V2\nls\ncd a\\\\ b\n/a.h5\tpwd\n"),
+            vec![
+                Entry {
+                    file: String::new(),
+                    command: "ls".to_string(),
+                },
+                Entry {
+                    file: String::new(),
+                    command: r"cd a\ b".to_string(),
+                },
+                Entry {
+                    file: "/a.h5".to_string(),
+                    command: "pwd".to_string(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn commands_of_file_includes_unknown_file() {
+        let history = history(&[("", "ls"), ("b.h5", "pwd")]);
+        assert_eq!(history.commands_of_file().collect::<Vec<_>>(), vec!["ls"]);
+    }
+
+    #[test]
+    fn save_keeps_legacy_history() {
+        let dir = std::env::temp_dir().join(format!("h5sh-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy_path = dir.join("history.txt");
+        let legacy = "#V2\nls\ncd entry\n";
+        std::fs::write(&legacy_path, legacy).unwrap();
+        let config = HistoryConfig {
+            path: Some(dir.join("history.tsv")),
+            ..HistoryConfig::default()
+        };
+
+        let mut history = History::new(&config, "a.h5".to_string());
+        history.legacy_path = Some(legacy_path.clone());
+        history.entries = history.read_entries().unwrap();
+        history.add("pwd");
+        history.save().unwrap();
+
+        let mut reloaded = History::new(&config, "b.h5".to_string());
+        reloaded.legacy_path = Some(legacy_path.clone());
+        reloaded.entries = reloaded.read_entries().unwrap();
+        let saved: Vec<_> = reloaded
+            .numbered()
+            .map(|(_, entry)| (entry.file.as_str(), entry.command.as_str()))
+            .collect();
+        let legacy_after = std::fs::read_to_string(&legacy_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(saved, vec![("", "ls"), ("", "cd entry"), ("a.h5", "pwd")]);
+        assert_eq!(legacy_after, legacy);
+    }
+
+    #[test]
+    fn save_does_not_overwrite_unreadable_file() {
+        let dir = std::env::temp_dir().join(format!("h5sh-history-invalid-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.tsv");
+        let content = b"a.h5\tls\n\xff\xfe\n";
+        std::fs::write(&path, content).unwrap();
+        let config = HistoryConfig {
+            path: Some(path.clone()),
+            ..HistoryConfig::default()
+        };
+
+        let mut history = History::load(&config, "a.h5".to_string());
+        history.add("pwd");
+        let result = history.save();
+        let after = std::fs::read(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(after, content);
+    }
+}
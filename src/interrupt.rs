@@ -0,0 +1,35 @@
+//! Cancel running commands with Ctrl-C.
+//!
+//! The terminal is not in raw mode while a command runs, so Ctrl-C sends SIGINT.
+//! Instead of terminating h5sh, the signal sets a flag that long-running commands
+//! check regularly to return to the prompt.
+//! A second Ctrl-C before the flag is cleared terminates h5sh
+//! in case a command does not check the flag.
+
+use signal_hook::consts::SIGINT;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Install the handler for Ctrl-C.
+pub fn install() -> std::io::Result<()> {
+    let flag = INTERRUPTED.get_or_init(Default::default);
+    // Registered first so that it sees the flag before the second handler sets it.
+    signal_hook::flag::register_conditional_shutdown(SIGINT, 130, Arc::clone(flag))?;
+    signal_hook::flag::register(SIGINT, Arc::clone(flag))?;
+    Ok(())
+}
+
+/// Forget previous interrupts, call before running a command.
+pub fn clear() {
+    if let Some(flag) = INTERRUPTED.get() {
+        flag.store(false, Ordering::Relaxed);
+    }
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED
+        .get()
+        .is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
@@ -1,8 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use super::super::parse::{Argument, Expression};
+use super::super::parse::{Argument, Expression, is_variable_char};
 use super::super::{text_index::TextIndex, text_range::TextRange};
-use crate::h5::{self, FileCache, H5Path};
+use super::fuzzy;
+use crate::config::CompletionMode;
+use crate::h5::{self, FileCache, H5Path, glob};
+
+/// Maximum number of objects that are searched when completing `**/name`.
+const MAX_DESCENDANTS: usize = 10_000;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Candidate {
@@ -31,30 +36,258 @@ impl Candidate {
     }
 }
 
-pub fn complete<CacheValue, Children, LoadChildren>(
+/// Names of shell entities other than paths that can be completed.
+#[derive(Clone, Copy, Debug)]
+pub struct Names<'a> {
+    pub commands: &'a HashSet<String>,
+    /// Argument parsers of commands, used for flags and their values.
+    pub arg_parsers: &'a HashMap<String, clap::Command>,
+    pub variables: &'a HashSet<String>,
+}
+
+/// Functions that load data from the file into the cache on demand.
+#[derive(Clone, Copy, Debug)]
+pub struct Loaders<LoadChildren, LoadAttrNames> {
+    pub children: LoadChildren,
+    pub attr_names: LoadAttrNames,
+}
+
+/// State of the shell and file that completions are computed from.
+pub struct Context<'a, CacheValue, LoadChildren, LoadAttrNames> {
+    pub names: Names<'a>,
+    pub file_cache: &'a mut FileCache<CacheValue>,
+    pub working_group: &'a H5Path,
+    pub mode: CompletionMode,
+    pub loaders: Loaders<LoadChildren, LoadAttrNames>,
+}
+
+pub fn complete<CacheValue, Children, LoadChildren, LoadAttrNames>(
     expression: &Expression,
     line: &str,
     pos: usize,
-    commands: &HashSet<String>,
-    file_cache: &mut FileCache<CacheValue>,
-    working_group: &H5Path,
-    load_children: LoadChildren,
+    context: Context<CacheValue, LoadChildren, LoadAttrNames>,
 ) -> rustyline::Result<(usize, Vec<Candidate>)>
 where
     LoadChildren: Fn(&CacheValue) -> h5::Result<Children>,
     Children: IntoIterator<Item = (H5Path, CacheValue, bool)>,
+    LoadAttrNames: Fn(&CacheValue) -> h5::Result<Vec<String>>,
 {
+    let Context {
+        names,
+        file_cache,
+        working_group,
+        mode,
+        loaders,
+    } = context;
     let pos = TextIndex::from(pos);
-    let candidates = match classify_location(expression, pos) {
+    let location = classify_location(expression, pos);
+    if let Some(name) = variable_prefix(location, line, pos) {
+        return Ok((pos.as_index(), variable_completions(name, names.variables)));
+    }
+    let arg_parser = match expression {
+        Expression::Call(call) => names.arg_parsers.get(call.function.get_content(line)),
+        _ => None,
+    };
+    let mut start = pos;
+    let candidates = match location {
         LocationType::Path(range) if pos == range.end() => {
-            path_completions(&line[range], file_cache, working_group, load_children)
+            if let Some(candidates) =
+                arg_parser.and_then(|parser| value_completions(expression, line, range, parser))
+            {
+                candidates
+            } else {
+                match glob::split_attr(&line[range]) {
+                    (location, Some(attr)) => attr_completions(
+                        location,
+                        attr,
+                        glob::unclosed_quote(&line[range]),
+                        file_cache,
+                        working_group,
+                        loaders,
+                    ),
+                    (_, None) => {
+                        let (offset, candidates) = path_completions(
+                            &line[range],
+                            mode,
+                            file_cache,
+                            working_group,
+                            loaders.children,
+                        );
+                        start = range.start() + TextIndex::from(offset);
+                        candidates
+                    }
+                }
+            }
         }
         LocationType::Command(range) if pos == range.end() => {
-            command_completions(&line[range], commands)
+            command_completions(&line[range], names.commands)
         }
+        LocationType::Other(range) if pos == range.end() => arg_parser
+            .map(|parser| flag_completions(&line[range], parser))
+            .unwrap_or_default(),
         _ => vec![],
     };
-    Ok((pos.as_index(), candidates))
+    Ok((start.as_index(), candidates))
+}
+
+/// Return the partial variable name if an argument ends in a variable reference.
+fn variable_prefix(location: LocationType, line: &str, pos: TextIndex) -> Option<&str> {
+    let (LocationType::Path(range) | LocationType::Other(range)) = location else {
+        return None;
+    };
+    if pos != range.end() {
+        return None;
+    }
+    let word = &line[range];
+    let (_, name) = word.rsplit_once('$')?;
+    name.chars().all(is_variable_char).then_some(name)
+}
+
+fn variable_completions(input: &str, variables: &HashSet<String>) -> Vec<Candidate> {
+    variables
+        .iter()
+        .filter_map(|name| {
+            name.strip_prefix(input).map(|stripped| Candidate {
+                display: format!("${name}"),
+                replacement: stripped.into(),
+            })
+        })
+        .collect()
+}
+
+/// Complete the name of a flag.
+///
+/// A single `-` lists all flags, `--` only long flags.
+/// After a group of short flags, the remaining short flags are listed.
+fn flag_completions(input: &str, parser: &clap::Command) -> Vec<Candidate> {
+    let flags = parser
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set());
+    if let Some(prefix) = input.strip_prefix("--") {
+        flags
+            .filter_map(|arg| {
+                let long = arg.get_long()?;
+                Some(Candidate {
+                    display: describe_flag(format!("--{long}"), arg),
+                    replacement: long.strip_prefix(prefix)?.to_string(),
+                })
+            })
+            .collect()
+    } else if input == "-" {
+        let (short, long): (Vec<_>, Vec<_>) = flags
+            .flat_map(|arg| {
+                let short = arg.get_short().map(|short| Candidate {
+                    display: describe_flag(format!("-{short}"), arg),
+                    replacement: short.to_string(),
+                });
+                let long = arg.get_long().map(|long| Candidate {
+                    display: describe_flag(format!("--{long}"), arg),
+                    replacement: format!("-{long}"),
+                });
+                [short, long]
+            })
+            .flatten()
+            .partition(|candidate| !candidate.replacement.starts_with('-'));
+        short.into_iter().chain(long).collect()
+    } else {
+        let cluster = &input[1..];
+        let expects_value = cluster.chars().last().is_some_and(|last| {
+            parser
+                .get_arguments()
+                .any(|arg| arg.get_short() == Some(last) && arg.get_action().takes_values())
+        });
+        if expects_value {
+            return Vec::new();
+        }
+        flags
+            .filter_map(|arg| {
+                let short = arg.get_short()?;
+                (!cluster.contains(short)).then(|| Candidate {
+                    display: describe_flag(format!("-{short}"), arg),
+                    replacement: short.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+fn describe_flag(flag: String, arg: &clap::Arg) -> String {
+    match arg
+        .get_help()
+        .and_then(|help| help.to_string().lines().next().map(String::from))
+    {
+        Some(help) => format!("{flag}  {help}"),
+        None => flag,
+    }
+}
+
+/// Complete the value of an option with a fixed set of values or a subcommand.
+///
+/// Returns `None` if the argument at `range` is neither of those.
+fn value_completions(
+    expression: &Expression,
+    line: &str,
+    range: TextRange,
+    parser: &clap::Command,
+) -> Option<Vec<Candidate>> {
+    let Expression::Call(call) = expression else {
+        return None;
+    };
+    let previous_arguments: Vec<_> = call
+        .arguments
+        .iter()
+        .filter(|arg| arg.range().end() <= range.start() && arg.range() != range)
+        .collect();
+    let input = glob::unquote(&line[range]);
+
+    let option = match previous_arguments.last() {
+        Some(Argument::Long(option)) => {
+            let name = option.get_content(line).strip_prefix("--")?;
+            parser
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(name))
+        }
+        Some(Argument::Short(option)) => {
+            let name = option.get_content(line).chars().last()?;
+            parser
+                .get_arguments()
+                .find(|arg| arg.get_short() == Some(name))
+        }
+        _ => None,
+    };
+    if let Some(option) = option
+        && option.get_action().takes_values()
+    {
+        let values = option.get_possible_values();
+        return (!values.is_empty()).then(|| {
+            values
+                .iter()
+                .filter(|value| !value.is_hide_set())
+                .filter_map(|value| candidate_from_value(value.get_name(), &input))
+                .collect()
+        });
+    }
+
+    let is_first_positional = !previous_arguments
+        .iter()
+        .any(|arg| matches!(arg, Argument::Plain(_)));
+    if parser.has_subcommands() && is_first_positional {
+        return Some(
+            parser
+                .get_subcommands()
+                .filter(|subcommand| !subcommand.is_hide_set())
+                .filter_map(|subcommand| candidate_from_value(subcommand.get_name(), &input))
+                .collect(),
+        );
+    }
+    None
+}
+
+fn candidate_from_value(value: &str, input: &str) -> Option<Candidate> {
+    value.strip_prefix(input).map(|stripped| Candidate {
+        display: value.into(),
+        replacement: glob::escape(stripped),
+    })
 }
 
 fn command_completions(input: &str, commands: &HashSet<String>) -> Vec<Candidate> {
@@ -64,7 +297,42 @@ fn command_completions(input: &str, commands: &HashSet<String>) -> Vec<Candidate
         .collect()
 }
 
+/// Complete a path.
+///
+/// Returns the candidates and the offset in `input` of the text they replace.
 fn path_completions<CacheValue, Children, LoadChildren>(
+    input: &str,
+    mode: CompletionMode,
+    file_cache: &mut FileCache<CacheValue>,
+    working_group: &H5Path,
+    load_children: LoadChildren,
+) -> (usize, Vec<Candidate>)
+where
+    LoadChildren: Fn(&CacheValue) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, CacheValue, bool)>,
+{
+    if let Some(index) = recursive_pattern_start(input) {
+        let candidates = recursive_path_completions(
+            &input[..index],
+            &input[index + "**/".len()..],
+            file_cache,
+            working_group,
+            load_children,
+        );
+        return (index, candidates);
+    }
+    match mode {
+        CompletionMode::Prefix => (
+            input.len(),
+            prefix_path_completions(input, file_cache, working_group, load_children),
+        ),
+        CompletionMode::Fuzzy => {
+            fuzzy_path_completions(input, file_cache, working_group, load_children)
+        }
+    }
+}
+
+fn prefix_path_completions<CacheValue, Children, LoadChildren>(
     input: &str,
     file_cache: &mut FileCache<CacheValue>,
     working_group: &H5Path,
@@ -76,20 +344,155 @@ where
 {
     use super::simple_completer::path_completions;
 
-    let current = working_group.join(&H5Path::from(input));
+    let current = working_group.join(&H5Path::from(glob::unquote(input)));
+    let quote = glob::unclosed_quote(input);
     path_completions(file_cache, &current, load_children)
         .into_iter()
         .map(|path| Candidate {
             display: path.name().to_string(),
-            replacement: path
-                .as_raw()
-                .strip_prefix(current.as_raw())
-                .unwrap_or("")
-                .to_string(),
+            replacement: escape_in_quote(
+                path.as_raw().strip_prefix(current.as_raw()).unwrap_or(""),
+                quote,
+            ),
         })
         .collect()
 }
 
+/// Complete the last segment of a path by fuzzy matching the names of the children.
+///
+/// The candidates replace the entire last segment.
+fn fuzzy_path_completions<CacheValue, Children, LoadChildren>(
+    input: &str,
+    file_cache: &mut FileCache<CacheValue>,
+    working_group: &H5Path,
+    load_children: LoadChildren,
+) -> (usize, Vec<Candidate>)
+where
+    LoadChildren: Fn(&CacheValue) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, CacheValue, bool)>,
+{
+    use super::simple_completer::children;
+
+    let segment_start = input.rfind('/').map_or(0, |index| index + 1);
+    let (group, segment) = input.split_at(segment_start);
+    let quote = glob::unclosed_quote(group);
+    let group = working_group
+        .join(&H5Path::from(glob::unquote(group)))
+        .resolve();
+    let children = children(file_cache, &group, load_children);
+    let candidates = fuzzy::rank(&glob::unquote(segment), children, |(path, _)| path.name())
+        .into_iter()
+        .map(|(path, is_group)| Candidate {
+            display: path.name().to_string(),
+            replacement: escape_in_quote(&with_group_slash(path.name(), is_group), quote),
+        })
+        .collect();
+    (segment_start, candidates)
+}
+
+/// Complete `**/query` by fuzzy matching the paths of all descendants of `group`.
+fn recursive_path_completions<CacheValue, Children, LoadChildren>(
+    group: &str,
+    query: &str,
+    file_cache: &mut FileCache<CacheValue>,
+    working_group: &H5Path,
+    load_children: LoadChildren,
+) -> Vec<Candidate>
+where
+    LoadChildren: Fn(&CacheValue) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, CacheValue, bool)>,
+{
+    use super::simple_completer::descendants;
+
+    let group = working_group
+        .join(&H5Path::from(glob::unquote(group)))
+        .resolve();
+    let descendants = descendants(file_cache, &group, load_children, MAX_DESCENDANTS)
+        .into_iter()
+        .map(|(path, is_group)| (path.relative_to(&group), is_group));
+    fuzzy::rank(&glob::unquote(query), descendants, |(path, _)| {
+        path.as_raw()
+    })
+    .into_iter()
+    .map(|(path, is_group)| {
+        let path = with_group_slash(path.as_raw(), is_group);
+        Candidate {
+            replacement: glob::escape(&path),
+            display: path,
+        }
+    })
+    .collect()
+}
+
+/// Return the index of a trailing `**/` segment in `input` if there is one.
+fn recursive_pattern_start(input: &str) -> Option<usize> {
+    let index = input.rfind("**/")?;
+    let before = &input[..index];
+    ((before.is_empty() || before.ends_with('/')) && glob::unclosed_quote(before).is_none())
+        .then_some(index)
+}
+
+fn with_group_slash(path: &str, is_group: bool) -> String {
+    if is_group {
+        format!("{path}/")
+    } else {
+        path.to_string()
+    }
+}
+
+/// Complete the name of an attribute of the object at `location`.
+///
+/// An empty location refers to the working group.
+fn attr_completions<CacheValue, Children, LoadChildren, LoadAttrNames>(
+    location: &str,
+    input: &str,
+    quote: Option<char>,
+    file_cache: &mut FileCache<CacheValue>,
+    working_group: &H5Path,
+    loaders: Loaders<LoadChildren, LoadAttrNames>,
+) -> Vec<Candidate>
+where
+    LoadChildren: Fn(&CacheValue) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, CacheValue, bool)>,
+    LoadAttrNames: Fn(&CacheValue) -> h5::Result<Vec<String>>,
+{
+    use super::simple_completer::attr_names;
+
+    let location = working_group
+        .join(&H5Path::from(glob::unquote(location)))
+        .resolve();
+    let input = glob::unquote(input);
+    attr_names(file_cache, &location, loaders.children, loaders.attr_names)
+        .into_iter()
+        .filter_map(|name| {
+            let replacement = escape_in_quote(name.strip_prefix(input.as_str())?, quote);
+            Some(Candidate {
+                display: name,
+                replacement,
+            })
+        })
+        .collect()
+}
+
+/// Escape text for insertion into a word that ends inside the given quote.
+fn escape_in_quote(text: &str, quote: Option<char>) -> String {
+    match quote {
+        // Nothing can be escaped in single quotes.
+        Some('\'') => text.to_string(),
+        Some(_) => {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                if matches!(c, '"' | '\\' | '$') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+        None => glob::escape(text),
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 enum LocationType {
     Path(TextRange),
@@ -127,15 +530,16 @@ fn classify_location_expression(expression: &Expression, pos: TextIndex) -> Opti
             LocationType::Path(string.range).some_if_contains(pos)
         }
         Expression::Call(call) => {
-            if !call.range.contains_or_end(pos) {
-                return None; // avoid scanning children
-            }
             if call.function.range.contains_or_end(pos) {
                 Some(LocationType::Command(call.function.range))
+            } else if pos < call.function.range.start() {
+                None
             } else {
+                // If no argument is at pos, a new one starts there.
                 call.arguments
                     .iter()
                     .find_map(|arg| classify_location_argument(arg, pos))
+                    .or(Some(LocationType::Path(TextRange::start_new(pos))))
             }
         }
         Expression::Noop => None,
@@ -158,6 +562,48 @@ mod tests {
     use pretty_assertions::assert_eq;
     use std::collections::HashMap;
 
+    type LoadAttrNames = fn(&i32) -> h5::Result<Vec<String>>;
+
+    fn loaders<LoadChildren>(children: LoadChildren) -> Loaders<LoadChildren, LoadAttrNames> {
+        Loaders {
+            children,
+            attr_names: |_| panic!("Do not load attribute names!"),
+        }
+    }
+
+    /// Complete commands and paths by prefix, without variables or argument parsers.
+    ///
+    /// This is all that most tests need.
+    fn complete<LoadChildren>(
+        expression: &Expression,
+        line: &str,
+        pos: usize,
+        commands: &HashSet<String>,
+        file_cache: &mut FileCache<i32>,
+        working_group: &H5Path,
+        load_children: LoadChildren,
+    ) -> rustyline::Result<(usize, Vec<Candidate>)>
+    where
+        LoadChildren: Fn(&i32) -> h5::Result<Vec<(H5Path, i32, bool)>>,
+    {
+        super::complete(
+            expression,
+            line,
+            pos,
+            Context {
+                names: Names {
+                    commands,
+                    arg_parsers: &HashMap::new(),
+                    variables: &HashSet::new(),
+                },
+                file_cache,
+                working_group,
+                mode: CompletionMode::Prefix,
+                loaders: loaders(load_children),
+            },
+        )
+    }
+
     fn failing_load_children(_value: &i32) -> h5::Result<Vec<(H5Path, i32, bool)>> {
         panic!("Do not load children!");
     }
@@ -272,6 +718,63 @@ mod tests {
         assert_eq!(completions, expected);
     }
 
+    fn complete_path_with_special_chars(line: &str) -> Vec<Candidate> {
+        let expression = Parser::new(line).parse();
+        let commands = HashSet::new();
+        let mut cache = FileCache::new();
+        cache.insert_group(&H5Path::from("/"), -1);
+
+        fn load_children(value: &i32) -> h5::Result<Vec<(H5Path, i32, bool)>> {
+            let entries = HashMap::from([(-1, vec![(H5Path::from("/my data@1"), 1, false)])]);
+            Ok(entries[value].clone())
+        }
+
+        let (_, completions) = complete(
+            &expression,
+            line,
+            line.len(),
+            &commands,
+            &mut cache,
+            &H5Path::root(),
+            load_children,
+        )
+        .unwrap();
+        completions
+    }
+
+    #[test]
+    fn complete_path_escapes_special_chars() {
+        assert_eq!(
+            complete_path_with_special_chars("ls my"),
+            vec![Candidate {
+                display: "my data@1".into(),
+                replacement: r"\ data\@1".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn complete_path_after_escaped_space() {
+        assert_eq!(
+            complete_path_with_special_chars(r"ls my\ d"),
+            vec![Candidate {
+                display: "my data@1".into(),
+                replacement: r"ata\@1".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn complete_path_in_quotes() {
+        assert_eq!(
+            complete_path_with_special_chars("ls 'my d"),
+            vec![Candidate {
+                display: "my data@1".into(),
+                replacement: "ata@1".into(),
+            }]
+        );
+    }
+
     #[test]
     fn complete_path_single_arg_absolute_in_cwd() {
         let line = "ls /pa";
@@ -423,4 +926,403 @@ mod tests {
         assert_eq!(insertion, 10);
         assert_eq!(completions, expected);
     }
+
+    fn complete_in_tree(line: &str, mode: CompletionMode) -> (usize, Vec<Candidate>) {
+        let expression = Parser::new(line).parse();
+        let commands = HashSet::new();
+        let mut cache = FileCache::new();
+        cache.insert_group(&H5Path::from("/"), -1);
+
+        fn load_children(value: &i32) -> h5::Result<Vec<(H5Path, i32, bool)>> {
+            let entries = HashMap::from([
+                (-1, vec![(H5Path::from("/entry"), 1, true)]),
+                (
+                    1,
+                    vec![
+                        (H5Path::from("/entry/instrument"), 2, true),
+                        (H5Path::from("/entry/sample"), 3, true),
+                    ],
+                ),
+                (
+                    2,
+                    vec![
+                        (H5Path::from("/entry/instrument/detector_1"), 4, true),
+                        (H5Path::from("/entry/instrument/detector_2"), 5, true),
+                        (H5Path::from("/entry/instrument/monitor"), 6, false),
+                    ],
+                ),
+                (3, vec![]),
+                (
+                    4,
+                    vec![(H5Path::from("/entry/instrument/detector_1/data"), 7, false)],
+                ),
+                (5, vec![]),
+            ]);
+            Ok(entries[value].clone())
+        }
+
+        super::complete(
+            &expression,
+            line,
+            line.len(),
+            Context {
+                names: Names {
+                    commands: &commands,
+                    arg_parsers: &HashMap::new(),
+                    variables: &HashSet::new(),
+                },
+                file_cache: &mut cache,
+                working_group: &H5Path::root(),
+                mode,
+                loaders: loaders(load_children),
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn complete_path_fuzzy() {
+        let (insertion, completions) =
+            complete_in_tree("ls /entry/instrument/dt2", CompletionMode::Fuzzy);
+        assert_eq!(insertion, 21);
+        assert_eq!(completions, vec![candidate("detector_2", "detector_2/")]);
+    }
+
+    #[test]
+    fn complete_path_fuzzy_ranks_matches() {
+        let (insertion, completions) =
+            complete_in_tree("ls /entry/instrument/tor", CompletionMode::Fuzzy);
+        assert_eq!(insertion, 21);
+        assert_eq!(
+            completions,
+            vec![
+                candidate("monitor", "monitor"),
+                candidate("detector_1", "detector_1/"),
+                candidate("detector_2", "detector_2/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_path_prefix_does_not_match_fuzzy() {
+        let (_, completions) = complete_in_tree("ls /entry/instrument/dt2", CompletionMode::Prefix);
+        assert_eq!(completions, vec![]);
+    }
+
+    #[test]
+    fn complete_path_recursive() {
+        let (insertion, completions) = complete_in_tree("cd **/det", CompletionMode::Prefix);
+        assert_eq!(insertion, 3);
+        assert_eq!(
+            completions,
+            vec![
+                candidate(
+                    "entry/instrument/detector_1/",
+                    "entry/instrument/detector_1/"
+                ),
+                candidate(
+                    "entry/instrument/detector_2/",
+                    "entry/instrument/detector_2/"
+                ),
+                // Matches the parent group.
+                candidate(
+                    "entry/instrument/detector_1/data",
+                    "entry/instrument/detector_1/data"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_path_recursive_in_group() {
+        let (insertion, completions) = complete_in_tree("cd /entry/**/data", CompletionMode::Fuzzy);
+        assert_eq!(insertion, 10);
+        assert_eq!(
+            completions,
+            vec![candidate(
+                "instrument/detector_1/data",
+                "instrument/detector_1/data"
+            )]
+        );
+    }
+
+    #[test]
+    fn complete_variable() {
+        let line = "ls $de";
+        let expression = Parser::new(line).parse();
+        let commands = HashSet::new();
+        let variables = HashSet::from(["det".into(), "data".into(), "PWD".into()]);
+        let mut cache = FileCache::new();
+        let cwd = H5Path::root();
+
+        let (insertion, completions) = super::complete(
+            &expression,
+            line,
+            6,
+            Context {
+                names: Names {
+                    commands: &commands,
+                    arg_parsers: &HashMap::new(),
+                    variables: &variables,
+                },
+                file_cache: &mut cache,
+                working_group: &cwd,
+                mode: CompletionMode::Prefix,
+                loaders: loaders(failing_load_children),
+            },
+        )
+        .unwrap();
+
+        let expected = vec![Candidate {
+            display: "$det".into(),
+            replacement: "t".into(),
+        }];
+        assert_eq!(insertion, 6);
+        assert_eq!(completions, expected);
+    }
+
+    #[test]
+    fn complete_variable_in_path() {
+        let line = "ls /entry/$";
+        let expression = Parser::new(line).parse();
+        let commands = HashSet::new();
+        let variables = HashSet::from(["det".into()]);
+        let mut cache = FileCache::new();
+        let cwd = H5Path::root();
+
+        let (_, completions) = super::complete(
+            &expression,
+            line,
+            11,
+            Context {
+                names: Names {
+                    commands: &commands,
+                    arg_parsers: &HashMap::new(),
+                    variables: &variables,
+                },
+                file_cache: &mut cache,
+                working_group: &cwd,
+                mode: CompletionMode::Prefix,
+                loaders: loaders(failing_load_children),
+            },
+        )
+        .unwrap();
+
+        let expected = vec![Candidate {
+            display: "$det".into(),
+            replacement: "det".into(),
+        }];
+        assert_eq!(completions, expected);
+    }
+
+    fn complete_attr(line: &str, working_group: &str) -> Vec<Candidate> {
+        let expression = Parser::new(line).parse();
+        let commands = HashSet::new();
+        let mut cache = FileCache::new();
+        cache.insert_group(&H5Path::from("/"), -1);
+
+        fn load_children(value: &i32) -> h5::Result<Vec<(H5Path, i32, bool)>> {
+            let entries = HashMap::from([
+                (-1, vec![(H5Path::from("/entry"), 1, true)]),
+                (1, vec![(H5Path::from("/entry/data"), 2, false)]),
+            ]);
+            Ok(entries[value].clone())
+        }
+
+        fn load_attr_names(value: &i32) -> h5::Result<Vec<String>> {
+            Ok(match value {
+                -1 => vec!["file_name".into()],
+                2 => vec!["units".into(), "long name".into()],
+                _ => vec![],
+            })
+        }
+
+        let (_, mut completions) = super::complete(
+            &expression,
+            line,
+            line.len(),
+            Context {
+                names: Names {
+                    commands: &commands,
+                    arg_parsers: &HashMap::new(),
+                    variables: &HashSet::new(),
+                },
+                file_cache: &mut cache,
+                working_group: &H5Path::from(working_group),
+                mode: CompletionMode::Prefix,
+                loaders: Loaders {
+                    children: load_children,
+                    attr_names: load_attr_names,
+                },
+            },
+        )
+        .unwrap();
+        completions.sort_by(|a, b| a.display.cmp(&b.display));
+        completions
+    }
+
+    #[test]
+    fn complete_attr_name() {
+        assert_eq!(
+            complete_attr("inspect entry/data@u", "/"),
+            vec![Candidate {
+                display: "units".into(),
+                replacement: "nits".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn complete_attr_name_all() {
+        assert_eq!(
+            complete_attr("inspect data@", "/entry"),
+            vec![
+                Candidate {
+                    display: "long name".into(),
+                    replacement: r"long\ name".into(),
+                },
+                Candidate {
+                    display: "units".into(),
+                    replacement: "units".into(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_attr_name_of_working_group() {
+        assert_eq!(
+            complete_attr("find @fi", "/"),
+            vec![Candidate {
+                display: "file_name".into(),
+                replacement: "le_name".into(),
+            }]
+        );
+    }
+
+    fn complete_with_parser(line: &str) -> Vec<Candidate> {
+        let expression = Parser::new(line).parse();
+        let commands = HashSet::from(["cmd".into()]);
+        let mut parser = clap::Command::new("cmd")
+            .arg(
+                clap::Arg::new("long")
+                    .short('l')
+                    .action(clap::ArgAction::SetTrue)
+                    .help("Long listing."),
+            )
+            .arg(
+                clap::Arg::new("name")
+                    .long("name")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                clap::Arg::new("color")
+                    .short('c')
+                    .long("color")
+                    .value_parser(["auto", "never"])
+                    .help("When to color.\nMore details."),
+            )
+            .arg(clap::Arg::new("path"))
+            .disable_help_flag(true);
+        parser.build();
+        let arg_parsers = HashMap::from([("cmd".to_string(), parser)]);
+        let mut cache = FileCache::new();
+        cache.insert_group(&H5Path::from("/"), -1);
+
+        fn load_children(value: &i32) -> h5::Result<Vec<(H5Path, i32, bool)>> {
+            let entries = HashMap::from([(-1, vec![(H5Path::from("/data"), 1, false)])]);
+            Ok(entries[value].clone())
+        }
+
+        let (_, completions) = super::complete(
+            &expression,
+            line,
+            line.len(),
+            Context {
+                names: Names {
+                    commands: &commands,
+                    arg_parsers: &arg_parsers,
+                    variables: &HashSet::new(),
+                },
+                file_cache: &mut cache,
+                working_group: &H5Path::root(),
+                mode: CompletionMode::Prefix,
+                loaders: loaders(load_children),
+            },
+        )
+        .unwrap();
+        completions
+    }
+
+    fn candidate(display: &str, replacement: &str) -> Candidate {
+        Candidate {
+            display: display.into(),
+            replacement: replacement.into(),
+        }
+    }
+
+    #[test]
+    fn complete_long_flag() {
+        assert_eq!(
+            complete_with_parser("cmd --co"),
+            vec![candidate("--color  When to color.", "lor")]
+        );
+    }
+
+    #[test]
+    fn complete_all_long_flags() {
+        assert_eq!(
+            complete_with_parser("cmd --"),
+            vec![
+                candidate("--name", "name"),
+                candidate("--color  When to color.", "color")
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_all_flags() {
+        assert_eq!(
+            complete_with_parser("cmd -"),
+            vec![
+                candidate("-l  Long listing.", "l"),
+                candidate("-c  When to color.", "c"),
+                candidate("--name", "-name"),
+                candidate("--color  When to color.", "-color")
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_short_flag_cluster() {
+        assert_eq!(
+            complete_with_parser("cmd -l"),
+            vec![candidate("-c  When to color.", "c")]
+        );
+        assert_eq!(complete_with_parser("cmd -lc"), vec![]);
+    }
+
+    #[test]
+    fn complete_option_value() {
+        assert_eq!(
+            complete_with_parser("cmd --color "),
+            vec![candidate("auto", "auto"), candidate("never", "never")]
+        );
+        assert_eq!(
+            complete_with_parser("cmd --color=n"),
+            vec![candidate("never", "ever")]
+        );
+        assert_eq!(
+            complete_with_parser("cmd -c a"),
+            vec![candidate("auto", "uto")]
+        );
+    }
+
+    #[test]
+    fn complete_path_after_flag() {
+        assert_eq!(
+            complete_with_parser("cmd -l "),
+            vec![candidate("data", "data")]
+        );
+    }
 }
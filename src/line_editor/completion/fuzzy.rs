@@ -0,0 +1,150 @@
+//! Fuzzy matching of names, similar to fzf.
+//!
+//! A query matches a text if the text contains all characters of the query in order,
+//! ignoring case. Matches are scored higher if the characters are consecutive or
+//! at the start of words, e.g., after `_` or `/`.
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_CONSECUTIVE: i32 = 8;
+const MAX_GAP_PENALTY: i32 = 8;
+
+/// Return the score of the best match of `query` in `text` or `None` if it does not match.
+///
+/// This takes time proportional to the product of the lengths of `query` and `text`.
+pub(super) fn score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<_> = query.chars().map(fold_case).collect();
+    let text: Vec<_> = text.chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // The gap penalty is the same for all gaps of at least this length.
+    let max_gap = MAX_GAP_PENALTY as usize;
+    // best[j] is the best score of the query so far with its last character matched
+    // at text[j].
+    let mut best: Vec<Option<i32>> = vec![None; text.len()];
+    for (i, &q) in query.iter().enumerate() {
+        let mut next = vec![None; text.len()];
+        // Best score in best[..j - max_gap], i.e., before a gap of at least max_gap.
+        let mut distant: Option<i32> = None;
+        for (j, &c) in text.iter().enumerate() {
+            if let Some(k) = j.checked_sub(max_gap + 1) {
+                distant = distant.max(best[k]);
+            }
+            if fold_case(c) != q {
+                continue;
+            }
+            let previous = if i == 0 {
+                Some(0)
+            } else {
+                (j.saturating_sub(max_gap)..j)
+                    .filter_map(|k| {
+                        let gap = (j - k - 1) as i32;
+                        let transition = if gap == 0 { BONUS_CONSECUTIVE } else { -gap };
+                        best[k].map(|score| score + transition)
+                    })
+                    .max()
+                    .max(distant.map(|score| score - MAX_GAP_PENALTY))
+            };
+            next[j] = previous.map(|score| score + SCORE_MATCH + boundary_bonus(&text, j));
+        }
+        best = next;
+    }
+    best.into_iter().flatten().max()
+}
+
+/// Return the items that match `query`, best matches first.
+///
+/// Items with equal scores are ordered by the length of their key and then
+/// alphabetically, so that shorter names come first.
+pub(super) fn rank<T>(
+    query: &str,
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let mut scored: Vec<_> = items
+        .into_iter()
+        .filter_map(|item| Some((score(query, key(&item))?, item)))
+        .collect();
+    scored.sort_by(|(score_a, a), (score_b, b)| {
+        let (a, b) = (key(a), key(b));
+        score_b
+            .cmp(score_a)
+            .then(a.len().cmp(&b.len()))
+            .then(a.cmp(b))
+    });
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+fn boundary_bonus(text: &[char], index: usize) -> i32 {
+    let Some(&previous) = index.checked_sub(1).and_then(|i| text.get(i)) else {
+        return BONUS_BOUNDARY;
+    };
+    let current = text[index];
+    let is_boundary =
+        !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase());
+    if is_boundary { BONUS_BOUNDARY } else { 0 }
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn score_requires_all_chars_in_order() {
+        assert!(score("det", "detector").is_some());
+        assert!(score("dtr", "detector").is_some());
+        assert!(score("ted", "detector").is_none());
+        assert!(score("detx", "detector").is_none());
+        assert!(score("a", "").is_none());
+    }
+
+    #[test]
+    fn score_empty_query_matches_everything() {
+        assert_eq!(score("", "detector"), Some(0));
+        assert_eq!(score("", ""), Some(0));
+    }
+
+    #[test]
+    fn score_ignores_case() {
+        assert_eq!(score("DET", "detector"), score("det", "detector"));
+        assert_eq!(score("det", "Detector"), score("det", "detector"));
+    }
+
+    #[test]
+    fn score_prefers_consecutive_chars() {
+        assert!(score("det", "detector").unwrap() > score("det", "d_e_t").unwrap());
+    }
+
+    #[test]
+    fn score_prefers_word_boundaries() {
+        assert!(score("d1", "detector_1").unwrap() > score("d1", "detector10").unwrap());
+        assert!(score("tof", "time_of_flight").unwrap() > score("tof", "photofield").unwrap());
+        assert!(score("ev", "rawEvents").unwrap() > score("ev", "never").unwrap());
+    }
+
+    #[test]
+    fn score_caps_gap_penalty() {
+        assert!(score("ab", "axxb").unwrap() > score("ab", "axxxxb").unwrap());
+        assert_eq!(
+            score("ab", &format!("a{}b", "x".repeat(9))),
+            score("ab", &format!("a{}b", "x".repeat(100)))
+        );
+    }
+
+    #[test]
+    fn rank_orders_by_score() {
+        let ranked = rank(
+            "det",
+            ["monitor", "d_e_t", "detector_2", "detector_1", "det"],
+            |name| name,
+        );
+        assert_eq!(ranked, vec!["det", "detector_1", "detector_2", "d_e_t"]);
+    }
+}
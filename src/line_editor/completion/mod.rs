@@ -1,4 +1,5 @@
 mod completer;
+mod fuzzy;
 mod simple_completer;
 
-pub(super) use completer::{Candidate, complete};
+pub(super) use completer::{Candidate, Context, Loaders, Names, complete};
@@ -27,6 +27,73 @@ where
     }
 }
 
+/// Return the children of the group at `path` and whether they are groups.
+pub(super) fn children<Value, LoadChildren, Children>(
+    cache: &mut FileCache<Value>,
+    path: &H5Path,
+    load_children: LoadChildren,
+) -> Vec<(H5Path, bool)>
+where
+    LoadChildren: Fn(&Value) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, Value, bool)>,
+{
+    get_all_children(path, cache, load_children)
+        .map(|children| {
+            children
+                .map(|(path, entry)| (path.clone(), !entry.is_leaf()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return the descendants of the group at `path` and whether they are groups.
+///
+/// Groups are loaded breadth first until `limit` descendants have been found.
+pub(super) fn descendants<Value, LoadChildren, Children>(
+    cache: &mut FileCache<Value>,
+    path: &H5Path,
+    load_children: LoadChildren,
+    limit: usize,
+) -> Vec<(H5Path, bool)>
+where
+    LoadChildren: Fn(&Value) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, Value, bool)>,
+{
+    let mut found = children(cache, path, &load_children);
+    let mut next = 0;
+    while next < found.len() && found.len() < limit {
+        let (path, is_group) = found[next].clone();
+        if is_group && let Some(grandchildren) = cache.children_of(&path, &load_children) {
+            found.extend(grandchildren);
+        }
+        next += 1;
+    }
+    found.truncate(limit);
+    found
+}
+
+/// Return the names of the attributes of the object at `path`.
+pub(super) fn attr_names<Value, LoadChildren, Children, LoadAttrNames>(
+    cache: &mut FileCache<Value>,
+    path: &H5Path,
+    load_children: LoadChildren,
+    load_attr_names: LoadAttrNames,
+) -> Vec<String>
+where
+    LoadChildren: Fn(&Value) -> h5::Result<Children>,
+    Children: IntoIterator<Item = (H5Path, Value, bool)>,
+    LoadAttrNames: Fn(&Value) -> h5::Result<Vec<String>>,
+{
+    if !cache.contains_key(path) {
+        // Loading the children of the parent inserts the object into the cache.
+        let _ = get_all_children(&path.parent(), cache, load_children);
+    }
+    cache
+        .attr_names_of(path, load_attr_names)
+        .map(<[String]>::to_vec)
+        .unwrap_or_default()
+}
+
 fn finalize_entry_path<Value>(path: &str, entry: &CacheEntry<Value>) -> H5Path {
     match entry {
         Leaf { .. } => path.into(),
@@ -402,4 +469,36 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn descendants_loads_groups() {
+        let mut cache = make_cache().unwrap();
+        let load_children = child_loader();
+        let results = descendants(&mut cache, &H5Path::from("/base"), &load_children, 100);
+        assert_unordered_eq(
+            results,
+            [
+                (H5Path::from("/base/aa"), true),
+                (H5Path::from("/base/bb"), true),
+                (H5Path::from("/base/ee"), false),
+                (H5Path::from("/base/aa/xx"), false),
+                (H5Path::from("/base/aa/yy"), true),
+                (H5Path::from("/base/aa/yy/z1"), false),
+                (H5Path::from("/base/aa/yy/z2"), false),
+                (H5Path::from("/base/aa/yy/z12"), false),
+                (H5Path::from("/base/bb/dd"), false),
+                (H5Path::from("/base/bb/d1"), false),
+                (H5Path::from("/base/bb/d12"), false),
+                (H5Path::from("/base/bb/cc"), true),
+            ],
+        );
+    }
+
+    #[test]
+    fn descendants_stops_at_limit() {
+        let mut cache = make_cache().unwrap();
+        let load_children = child_loader();
+        let results = descendants(&mut cache, &H5Path::from("/base"), &load_children, 4);
+        assert_eq!(results.len(), 4);
+    }
 }
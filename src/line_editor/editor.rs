@@ -1,92 +1,163 @@
 use super::completion;
-use super::parse::{Argument, Expression, Parser, StringExpression};
+use super::parse::{
+    self, Argument, CallExpression, Continuation, Expression, Parser, StringExpression,
+};
 use super::text_index::TextIndex;
-use crate::h5::{self, CacheValue, H5Error, H5File, H5FileCache, H5Object, H5Path};
-use crate::output::Style;
+use crate::config::{CompletionMode, Config, Dedup};
+use crate::h5::{Existence, H5File, H5FileCache, H5Path, glob};
+use crate::history::History;
+use crate::output::{Printer, Style, style::Item};
 use crate::prompt::Prompt;
 use crate::shell::Shell;
+use bumpalo::Bump;
 use crossterm::{ExecutableCommand, style::Print};
-use log::{error, info};
+use hdf5::types::TypeDescriptor;
 use rustyline::{
-    CompletionType, Context, Helper, Hinter, Validator,
+    Cmd, CompletionType, ConditionalEventHandler, Context, Event, EventContext, EventHandler,
+    Helper, RepeatCount, Validator,
     completion::Completer,
-    config::{BellStyle, Config, EditMode},
+    config::{self, BellStyle},
     error::ReadlineError,
     highlight::{CmdKind, Highlighter},
+    hint::HistoryHinter,
     history::DefaultHistory,
 };
 use std::borrow::Cow;
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::DerefMut;
+use std::sync::{Arc, Mutex};
 
 type UnderlyingEditor<'f> = rustyline::Editor<Hinter<'f>, DefaultHistory>;
 
 pub struct LineEditor<'f> {
     editor: UnderlyingEditor<'f>,
     prompt: Prompt,
+    /// Set by key bindings to the command they run and the line that was being edited.
+    bound_command: Arc<Mutex<Option<BoundCommand>>>,
+    /// Line to continue editing after a bound command has run.
+    interrupted_line: Option<String>,
 }
 
 impl<'f> LineEditor<'f> {
     pub fn new(
         commands: HashSet<String>,
+        arg_parsers: HashMap<String, clap::Command>,
         file: &'f H5File,
         style: &Style,
+        config: &Config,
+        history: &History,
     ) -> rustyline::Result<Self> {
-        let mut editor = UnderlyingEditor::with_config(configuration()?)?;
+        let mut editor = UnderlyingEditor::with_config(configuration(config)?)?;
 
-        let hinter = match Hinter::new(commands, file, style.clone()) {
-            Ok(hinter) => hinter,
-            Err(err) => {
-                error!("Failed to create hinter: {err}");
-                return Err(ReadlineError::Interrupted);
-            }
-        };
+        let hinter = Hinter::new(
+            commands,
+            arg_parsers,
+            file,
+            style.clone(),
+            config.editor.completion,
+        );
         editor.set_helper(Some(hinter));
 
-        if editor.load_history(&history_path()).is_err() {
-            info!("No previous history.");
+        let bound_command = Arc::new(Mutex::new(None));
+        for (key, command) in &config.keybindings {
+            editor.bind_sequence(
+                key.0,
+                EventHandler::Conditional(Box::new(KeyBinding {
+                    command: command.clone(),
+                    bound_command: Arc::clone(&bound_command),
+                })),
+            );
+        }
+
+        let commands: Vec<_> = if config.history.all_files {
+            history.commands().collect()
+        } else {
+            history.commands_of_file().collect()
+        };
+        for command in commands {
+            let _ = editor.add_history_entry(command);
         }
         Ok(Self {
             editor,
-            prompt: Prompt::new(style),
+            prompt: Prompt::new(style, &config.prompt),
+            bound_command,
+            interrupted_line: None,
         })
     }
 
     pub fn poll(&mut self, shell: &Shell, h5file: &H5File) -> Poll {
-        let line = self.editor.readline(&self.prompt.render(shell, h5file));
-        match line {
-            Ok(line) => {
-                if line.is_empty() {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.working_group = shell.get_working_group().clone();
+            helper.variables = shell.variable_names().map(String::from).collect();
+            helper.load_working_group();
+        }
+        let prompt = self.prompt.render(shell, h5file);
+        let line = match self.interrupted_line.take() {
+            Some(initial) => self.editor.readline_with_initial(&prompt, (&initial, "")),
+            None => self.editor.readline(&prompt),
+        };
+        let result = match line {
+            Ok(line) => self.continue_input(line),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(input) => {
+                if input.is_empty() {
                     Poll::Skip
                 } else {
-                    self.add_history_entry(line.as_str());
-                    Poll::Cmd(line)
+                    Poll::Cmd(input)
+                }
+            }
+            Err(ReadlineError::Interrupted) => {
+                let bound = self
+                    .bound_command
+                    .lock()
+                    .ok()
+                    .and_then(|mut bound| bound.take());
+                match bound {
+                    Some(BoundCommand { command, line }) => {
+                        self.interrupted_line = Some(line).filter(|line| !line.is_empty());
+                        Poll::Binding(command)
+                    }
+                    None => Poll::Skip,
                 }
             }
-            Err(ReadlineError::Interrupted) => Poll::Skip,
             Err(ReadlineError::Eof) => Poll::Exit,
             Err(err) => Poll::Error(err.to_string()),
         }
     }
 
-    pub fn save_history(&mut self) -> rustyline::Result<()> {
-        let path = history_path();
-        // The history is never in the root dir.
-        let parent = path.parent().unwrap();
-        if !parent.exists() {
-            std::fs::create_dir_all(parent)?;
-        }
-        self.editor.save_history(&path)
-    }
-
-    pub fn set_working_group(&mut self, group: H5Path) {
+    /// Read more lines until the input is complete.
+    ///
+    /// A backslash at the end of a line is removed and the lines are joined.
+    /// Lines with unclosed quotes or brackets are joined with a line break.
+    fn continue_input(&mut self, mut input: String) -> rustyline::Result<String> {
+        let result = loop {
+            match parse::continuation(&input) {
+                Some(Continuation::Backslash) => {
+                    input.pop();
+                }
+                Some(Continuation::Unclosed) => input.push('\n'),
+                None => break Ok(input),
+            }
+            if let Some(helper) = self.editor.helper_mut() {
+                helper.continued_input.clone_from(&input);
+            }
+            match self.editor.readline(self.prompt.continuation()) {
+                Ok(line) => input.push_str(&line),
+                // Ctrl-D cancels the input instead of exiting the shell.
+                Err(ReadlineError::Eof) => break Ok(String::new()),
+                Err(err) => break Err(err),
+            }
+        };
         if let Some(helper) = self.editor.helper_mut() {
-            helper.working_group = group;
+            helper.continued_input.clear();
         }
+        result
     }
 
-    fn add_history_entry<S: AsRef<str> + Into<String>>(&mut self, entry: S) {
+    /// Add an entry to the history that can be recalled while editing.
+    pub fn add_history_entry<S: AsRef<str> + Into<String>>(&mut self, entry: S) {
         let _ = self.editor.add_history_entry(entry);
     }
 }
@@ -94,28 +165,199 @@ impl<'f> LineEditor<'f> {
 #[derive(Debug)]
 pub enum Poll {
     Cmd(String),
+    /// A command that was run by a key binding.
+    Binding(String),
     Error(String),
     Skip,
     Exit,
 }
 
-#[derive(Helper, Hinter, Validator)]
+struct BoundCommand {
+    command: String,
+    line: String,
+}
+
+/// Runs a command when a key is pressed.
+///
+/// Rustyline cannot submit a different line than the one being edited.
+/// So the binding interrupts editing and the editor restores the line afterward.
+struct KeyBinding {
+    command: String,
+    bound_command: Arc<Mutex<Option<BoundCommand>>>,
+}
+
+impl ConditionalEventHandler for KeyBinding {
+    fn handle(
+        &self,
+        _evt: &Event,
+        _n: RepeatCount,
+        _positive: bool,
+        ctx: &EventContext,
+    ) -> Option<Cmd> {
+        let mut bound = self.bound_command.lock().ok()?;
+        *bound = Some(BoundCommand {
+            command: self.command.clone(),
+            line: ctx.line().to_string(),
+        });
+        Some(Cmd::Interrupt)
+    }
+}
+
+#[derive(Helper, Validator)]
 struct Hinter<'f> {
     commands: HashSet<String>,
+    arg_parsers: HashMap<String, clap::Command>,
+    variables: HashSet<String>,
+    /// Also provides the cache of objects in the file.
     file: &'f H5File,
-    file_cache: RefCell<H5FileCache>,
     working_group: H5Path,
     style: Style,
+    completion_mode: CompletionMode,
+    /// Formats hints without styles, they are styled as a whole by `highlight_hint`.
+    printer: Printer,
+    /// Input from previous lines if the current line continues a command.
+    continued_input: String,
 }
 
 impl<'f> Hinter<'f> {
-    fn new(commands: HashSet<String>, file: &'f H5File, style: Style) -> h5::Result<Self> {
-        Ok(Self {
+    fn new(
+        commands: HashSet<String>,
+        arg_parsers: HashMap<String, clap::Command>,
+        file: &'f H5File,
+        style: Style,
+        completion_mode: CompletionMode,
+    ) -> Self {
+        Self {
             commands,
+            arg_parsers,
+            variables: HashSet::new(),
             file,
-            file_cache: H5FileCache::with_root(file)?.into(),
             working_group: H5Path::root(),
             style,
+            completion_mode,
+            printer: Printer::new(false),
+            continued_input: String::new(),
+        }
+    }
+
+    /// Load the working group and its ancestors into the cache.
+    ///
+    /// This happens before reading a line such that highlighting paths relative to the
+    /// working group does not need to load anything.
+    fn load_working_group(&self) {
+        self.file.cache_group(&self.working_group);
+    }
+
+    /// Return the one-line description of a command.
+    fn command_hint(&self, name: &str) -> Option<String> {
+        let about = self.arg_parsers.get(name)?.get_about()?.to_string();
+        about.lines().next().map(String::from)
+    }
+
+    /// Describe the object at the path given by an argument.
+    ///
+    /// Returns the shape and dtype of datasets and attributes
+    /// and the number of children of groups.
+    /// The description is taken from the cache so that redrawing the line does not
+    /// open objects in the file. Objects are only loaded the first time.
+    fn object_hint(&self, argument: &str) -> Option<String> {
+        if argument.contains('$') || glob::is_pattern(argument) {
+            return None;
+        }
+        let (location, attr) = glob::split_attr(argument);
+        let path = self
+            .working_group
+            .join(&H5Path::from(glob::unquote(location)))
+            .resolve();
+        let mut existence = self.file.try_cache()?.existence(&path);
+        if existence == Existence::Unknown {
+            self.file.cache_group(&path.parent());
+            existence = self.file.try_cache()?.existence(&path);
+        }
+        match (existence, attr) {
+            (Existence::Group | Existence::Leaf, Some(attr)) => {
+                let info = self.file.attr_info(&path, &glob::unquote(attr))?;
+                Some(self.describe_container(&info.shape, info.dtype.as_ref()))
+            }
+            (Existence::Group, None) => Some(match self.file.n_children(&path)? {
+                1 => "1 child".to_string(),
+                n => format!("{n} children"),
+            }),
+            (Existence::Leaf, None) => {
+                let info = self.file.dataset_info(&path)?;
+                Some(self.describe_container(&info.shape, info.dtype.as_ref()))
+            }
+            (Existence::Missing | Existence::Unknown, _) => None,
+        }
+    }
+
+    fn describe_container(&self, shape: &[usize], dtype: Option<&TypeDescriptor>) -> String {
+        let shape = shape
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let bump = Bump::new();
+        match dtype {
+            Some(descriptor) => {
+                format!("({shape}) {}", self.printer.format_dtype(descriptor, &bump))
+            }
+            None => format!("({shape})"),
+        }
+    }
+}
+
+/// Text that is shown after the cursor.
+struct Hint {
+    text: String,
+    /// Whether the text can be inserted into the line, e.g., with the right arrow key.
+    insertable: bool,
+}
+
+impl rustyline::hint::Hint for Hint {
+    fn display(&self) -> &str {
+        &self.text
+    }
+
+    fn completion(&self) -> Option<&str> {
+        self.insertable.then_some(self.text.as_str())
+    }
+}
+
+impl<'f> rustyline::hint::Hinter for Hinter<'f> {
+    type Hint = Hint;
+
+    /// Suggest the rest of the line from the history or describe what was typed.
+    ///
+    /// Describes the command if only a command name was typed
+    /// or the object at the path in the last argument.
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<Hint> {
+        if pos < line.len() || !self.continued_input.is_empty() {
+            return None;
+        }
+        if let Some(text) = HistoryHinter::new().hint(line, pos, ctx) {
+            return Some(Hint {
+                text,
+                insertable: true,
+            });
+        }
+
+        let Expression::Call(call) = Parser::new(line).parse() else {
+            return None;
+        };
+        let pos = TextIndex::from(pos);
+        let description = match call.arguments.last() {
+            None if call.function.range.end() == pos => {
+                self.command_hint(call.function.get_content(line))
+            }
+            Some(Argument::Plain(argument)) if argument.range.end() == pos => {
+                self.object_hint(argument.get_content(line))
+            }
+            _ => None,
+        }?;
+        Some(Hint {
+            text: format!("  {description}"),
+            insertable: false,
         })
     }
 }
@@ -129,52 +371,79 @@ impl<'f> Completer for Hinter<'f> {
         pos: usize,
         _ctx: &Context<'_>,
     ) -> rustyline::Result<(usize, Vec<Self::Candidate>)> {
-        let expression = Parser::new(line).parse();
-
-        let child_loader =
-            move |parent: &CacheValue| match self.file.load(*parent.location_token())? {
-                H5Object::Group(group) => {
-                    Ok(group
-                        .load_child_locations()?
-                        .into_iter()
-                        .map(|(path, location_info)| {
-                            (
-                                path,
-                                CacheValue::from_location_info(location_info),
-                                matches!(location_info.loc_type, hdf5::LocationType::Group),
-                            )
-                        }))
-                }
-                _ => Err(H5Error::Other("Not a group".into())),
-            };
+        // Complete in the context of the whole command if this line continues it.
+        let offset = self.continued_input.len();
+        let input = format!("{}{line}", self.continued_input);
+        let expression = Parser::new(&input).parse();
 
-        let mut file_cache = self.file_cache.borrow_mut();
+        let mut file_cache = self.file.cache();
 
-        completion::complete(
+        let (start, candidates) = completion::complete(
             &expression,
-            line,
-            pos,
-            &self.commands,
-            file_cache.deref_mut(),
-            &self.working_group,
-            child_loader,
-        )
+            &input,
+            offset + pos,
+            completion::Context {
+                names: completion::Names {
+                    commands: &self.commands,
+                    arg_parsers: &self.arg_parsers,
+                    variables: &self.variables,
+                },
+                file_cache: file_cache.deref_mut(),
+                working_group: &self.working_group,
+                mode: self.completion_mode,
+                loaders: completion::Loaders {
+                    children: H5FileCache::child_loader(self.file),
+                    attr_names: H5FileCache::attr_name_loader(self.file),
+                },
+            },
+        )?;
+        match start.checked_sub(offset) {
+            Some(start) => Ok((start, candidates)),
+            // The completed word starts on a previous line which cannot be edited anymore.
+            None => Ok((pos, Vec::new())),
+        }
     }
 }
 
 impl<'f> Highlighter for Hinter<'f> {
     fn highlight<'l>(&self, line: &'l str, _: usize) -> Cow<'l, str> {
+        // A continued line is not a command by itself and cannot be highlighted as one.
+        if !self.continued_input.is_empty() {
+            return Cow::Borrowed(line);
+        }
         let expression = Parser::new(line).parse();
+        // The cache is only borrowed mutably while completing, skip highlighting then.
+        let Some(file_cache) = self.file.try_cache() else {
+            return Cow::Borrowed(line);
+        };
 
-        if let Ok(highlighted) =
-            InputHighlighter::new(&self.commands).highlight(&expression, line, &self.style)
-        {
+        let highlighter = InputHighlighter::new(
+            &self.commands,
+            &self.arg_parsers,
+            &file_cache,
+            &self.working_group,
+        );
+        if let Ok(highlighted) = highlighter.highlight(&expression, line, &self.style) {
             Cow::Owned(highlighted)
         } else {
             Cow::Borrowed(line)
         }
     }
 
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        let style = &self.style.editor.hint;
+        let mut buffer: Vec<u8> = Vec::new();
+        let styled = buffer
+            .execute(style)
+            .and_then(|buffer| buffer.execute(Print(hint)))
+            .and_then(|buffer| buffer.execute(style.reset()))
+            .is_ok();
+        match String::from_utf8(buffer) {
+            Ok(highlighted) if styled => Cow::Owned(highlighted),
+            _ => Cow::Borrowed(hint),
+        }
+    }
+
     fn highlight_char(&self, line: &str, pos: usize, kind: CmdKind) -> bool {
         // This could be optimized further to not highlight if pos is in a plain
         // argument or some other string that does not get highlighted. But that would
@@ -190,14 +459,26 @@ struct InputHighlighter<'a> {
     buffer: Vec<u8>,
     pos: TextIndex,
     commands: &'a HashSet<String>,
+    arg_parsers: &'a HashMap<String, clap::Command>,
+    /// Only objects in the cache are highlighted to avoid blocking on the file.
+    file_cache: &'a H5FileCache,
+    working_group: &'a H5Path,
 }
 
 impl<'a> InputHighlighter<'a> {
-    fn new(commands: &'a HashSet<String>) -> Self {
+    fn new(
+        commands: &'a HashSet<String>,
+        arg_parsers: &'a HashMap<String, clap::Command>,
+        file_cache: &'a H5FileCache,
+        working_group: &'a H5Path,
+    ) -> Self {
         Self {
             buffer: Vec::default(),
             pos: TextIndex::default(),
             commands,
+            arg_parsers,
+            file_cache,
+            working_group,
         }
     }
 
@@ -228,8 +509,10 @@ impl<'a> InputHighlighter<'a> {
                     &style.editor.bad_command
                 };
                 self.highlight_string(&call.function, function_style, src)?;
-                for arg in &call.arguments {
-                    self.highlight_argument(arg, src, style)?;
+                let parser = self.arg_parsers.get(call.function.get_content(src));
+                let is_path = path_arguments(call, src, parser);
+                for (arg, is_path) in call.arguments.iter().zip(is_path) {
+                    self.highlight_argument(arg, is_path, src, style)?;
                 }
             }
             Expression::String(string) => {
@@ -243,10 +526,15 @@ impl<'a> InputHighlighter<'a> {
     fn highlight_argument(
         &mut self,
         arg: &Argument,
+        is_path: bool,
         src: &str,
         style: &Style,
     ) -> std::io::Result<()> {
         match arg {
+            Argument::Plain(string) if is_path => {
+                let path_style = self.path_style(string.get_content(src), style);
+                self.highlight_string(string, path_style, src)?;
+            }
             Argument::Plain(string) => {
                 self.highlight_string(string, &style.editor.pos_argument, src)?;
             }
@@ -260,10 +548,35 @@ impl<'a> InputHighlighter<'a> {
         Ok(())
     }
 
+    /// Select the style of a path by whether it exists according to the cache.
+    ///
+    /// Paths that the cache does not know about use the regular argument style.
+    fn path_style<'s>(&self, argument: &str, style: &'s Style) -> &'s Item {
+        if argument.contains('$') || glob::is_pattern(argument) {
+            return &style.editor.pos_argument;
+        }
+        let (location, attr) = glob::split_attr(argument);
+        let path = self
+            .working_group
+            .join(&H5Path::from(glob::unquote(location)))
+            .resolve();
+        match (self.file_cache.existence(&path), attr) {
+            (Existence::Missing, _) => &style.error,
+            (Existence::Unknown, _) => &style.editor.pos_argument,
+            (_, Some(attr)) => match self.file_cache.cached_attr_names(&path) {
+                Some(names) if names.contains(&glob::unquote(attr)) => &style.attribute,
+                Some(_) => &style.error,
+                None => &style.editor.pos_argument,
+            },
+            (Existence::Group, None) => &style.group,
+            (Existence::Leaf, None) => &style.dataset,
+        }
+    }
+
     fn highlight_string(
         &mut self,
         string: &StringExpression,
-        style: &crate::output::style::Item,
+        style: &Item,
         src: &str,
     ) -> std::io::Result<()> {
         self.unstyled_to(string.range.start(), src)?;
@@ -285,16 +598,212 @@ impl<'a> InputHighlighter<'a> {
     }
 }
 
-fn configuration() -> rustyline::Result<Config> {
-    Ok(Config::builder()
-        .history_ignore_dups(true)?
+/// Return whether each argument of a call is a path according to the command's parser.
+fn path_arguments(call: &CallExpression, src: &str, parser: Option<&clap::Command>) -> Vec<bool> {
+    let Some(parser) = parser else {
+        return vec![false; call.arguments.len()];
+    };
+    // Arguments after '--' belong to the `last` positional if there is one.
+    let (last, mut positionals): (Vec<_>, Vec<_>) =
+        parser.get_positionals().partition(|arg| arg.is_last_set());
+    let mut n_positionals = 0;
+    let mut expects_value = false;
+    call.arguments
+        .iter()
+        .map(|arg| match arg {
+            Argument::Long(option) => {
+                let name = option.get_content(src).trim_end().trim_start_matches('-');
+                if name.is_empty() && !last.is_empty() {
+                    positionals = last.clone();
+                    n_positionals = 0;
+                }
+                expects_value = parser
+                    .get_arguments()
+                    .any(|arg| arg.get_long() == Some(name) && arg.get_action().takes_values());
+                false
+            }
+            Argument::Short(option) => {
+                let last = option.get_content(src).chars().last();
+                expects_value = parser
+                    .get_arguments()
+                    .any(|arg| arg.get_short() == last && arg.get_action().takes_values());
+                false
+            }
+            Argument::Plain(_) if expects_value => {
+                expects_value = false;
+                false
+            }
+            Argument::Plain(_) => {
+                let positional = positionals.get(n_positionals).or_else(|| {
+                    positionals
+                        .last()
+                        .filter(|arg| matches!(arg.get_action(), clap::ArgAction::Append))
+                });
+                n_positionals += 1;
+                positional.is_some_and(|arg| arg.get_value_hint() == clap::ValueHint::AnyPath)
+            }
+        })
+        .collect()
+}
+
+fn configuration(config: &Config) -> rustyline::Result<config::Config> {
+    Ok(config::Config::builder()
+        .max_history_size(config.history.size)?
+        .history_ignore_dups(config.history.dedup != Dedup::None)?
         .history_ignore_space(true)
         .completion_type(CompletionType::List)
-        .edit_mode(EditMode::Emacs)
+        .edit_mode(config.editor.edit_mode.into())
         .bell_style(BellStyle::None)
         .build())
 }
 
-fn history_path() -> std::path::PathBuf {
-    dirs::cache_dir().unwrap().join("h5sh").join("history.txt")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::h5::OpenOptions;
+    use clap::{Arg, ArgAction, ValueHint};
+    use pretty_assertions::assert_eq;
+    use rustyline::hint::Hinter as _;
+    use std::path::PathBuf;
+
+    fn open_test_file() -> H5File {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/test.h5");
+        H5File::open(path, OpenOptions::default()).unwrap()
+    }
+
+    fn hinter(file: &H5File) -> Hinter<'_> {
+        let parser = clap::Command::new("ls").about("List children.\nMore details.");
+        Hinter::new(
+            HashSet::from(["ls".into()]),
+            HashMap::from([("ls".into(), parser)]),
+            file,
+            Style::new(false),
+            CompletionMode::Prefix,
+        )
+    }
+
+    #[test]
+    fn hint_command() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        assert_eq!(hinter.command_hint("ls"), Some("List children.".into()));
+        assert_eq!(hinter.command_hint("cd"), None);
+    }
+
+    #[test]
+    fn hint_group() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        assert_eq!(hinter.object_hint("base"), Some("7 children".into()));
+        assert_eq!(
+            hinter.object_hint("/base/sub-group"),
+            Some("1 child".into())
+        );
+    }
+
+    #[test]
+    fn hint_dataset() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        assert_eq!(
+            hinter.object_hint("base/arrays/ints"),
+            Some("(4) i64".into())
+        );
+    }
+
+    #[test]
+    fn hint_attribute() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        assert_eq!(
+            hinter.object_hint("base/sub-group@array"),
+            Some("(4) i64".into())
+        );
+        assert_eq!(hinter.object_hint("base/sub-group@missing"), None);
+    }
+
+    #[test]
+    fn hint_relative_to_working_group() {
+        let file = open_test_file();
+        let mut hinter = hinter(&file);
+        hinter.working_group = H5Path::from("/base/arrays");
+        assert_eq!(hinter.object_hint("ints"), Some("(4) i64".into()));
+        assert_eq!(hinter.object_hint("../sub-group"), Some("1 child".into()));
+    }
+
+    #[test]
+    fn hint_missing_object() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        assert_eq!(hinter.object_hint("base/missing"), None);
+        assert_eq!(hinter.object_hint("missing/data"), None);
+    }
+
+    #[test]
+    fn hint_loads_objects_into_cache() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        let path = H5Path::from("/base/arrays/ints");
+        assert_eq!(
+            file.try_cache().unwrap().existence(&path),
+            Existence::Unknown
+        );
+        hinter.object_hint("base/arrays/ints");
+        assert_eq!(file.try_cache().unwrap().existence(&path), Existence::Leaf);
+    }
+
+    #[test]
+    fn hint_last_argument_of_line() {
+        let file = open_test_file();
+        let hinter = hinter(&file);
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let line = "ls base/arrays/ints";
+        let hint = hinter.hint(line, line.len(), &ctx).unwrap();
+        assert_eq!(hint.text, "  (4) i64");
+        assert!(!hint.insertable);
+        assert!(hinter.hint("ls base/arrays/ints", 3, &ctx).is_none());
+    }
+
+    fn path_arguments_of(src: &str) -> Vec<bool> {
+        let parser = clap::Command::new("cmd")
+            .arg(
+                Arg::new("paths")
+                    .action(ArgAction::Append)
+                    .value_hint(ValueHint::AnyPath),
+            )
+            .arg(Arg::new("command").action(ArgAction::Append).last(true))
+            .arg(Arg::new("sort").short('s').long("sort"))
+            .arg(
+                Arg::new("long")
+                    .short('l')
+                    .long("long")
+                    .action(ArgAction::SetTrue),
+            );
+        let Expression::Call(call) = Parser::new(src).parse() else {
+            panic!("Expected a call");
+        };
+        path_arguments(&call, src, Some(&parser))
+    }
+
+    #[test]
+    fn path_arguments_positional() {
+        assert_eq!(path_arguments_of("cmd a b"), vec![true, true]);
+    }
+
+    #[test]
+    fn path_arguments_skip_option_values() {
+        assert_eq!(
+            path_arguments_of("cmd -l a --sort name b -s size"),
+            vec![false, true, false, false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn path_arguments_after_separator() {
+        assert_eq!(
+            path_arguments_of("cmd a -- ls b"),
+            vec![true, false, false, false]
+        );
+    }
 }
@@ -28,6 +28,17 @@ pub enum Argument {
     Short(StringExpression),
 }
 
+/// Part of a word that is subject to expansion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WordPart<'s> {
+    /// Text that is used as is.
+    Literal(&'s str),
+    /// Reference to a variable, `$name` or `${name}`, holds the name.
+    Variable(&'s str),
+    /// Command substitution, `$(command)`, holds the command.
+    Substitution(&'s str),
+}
+
 impl CallExpression {
     pub fn get_args_str<'s>(&self, src: &'s str) -> &'s str {
         &src[self.function.range.end().as_index()..self.range.end().as_index()]
@@ -54,6 +65,135 @@ impl Argument {
     }
 }
 
+/// Split a word into literal text, variable references, and command substitutions.
+///
+/// A `$` that does not start a reference or substitution is a literal.
+/// So is a `$` in single quotes or after a backslash.
+/// Quotes are kept in the literal parts.
+/// An unclosed substitution extends to the end of the word.
+pub fn split_word(word: &str) -> Vec<WordPart<'_>> {
+    let mut parts = Vec::new();
+    let mut literal_start = 0;
+    let mut quote = None;
+    let mut index = 0;
+    while let Some(c) = word[index..].chars().next() {
+        let next = index + c.len_utf8();
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some('\''), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (_, '\\') => {
+                index = next + word[next..].chars().next().map_or(0, char::len_utf8);
+                continue;
+            }
+            (_, '$') => {
+                if let Some((part, consumed)) = parse_expansion(&word[next..]) {
+                    if literal_start < index {
+                        parts.push(WordPart::Literal(&word[literal_start..index]));
+                    }
+                    parts.push(part);
+                    index = next + consumed;
+                    literal_start = index;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        index = next;
+    }
+    if literal_start < word.len() {
+        parts.push(WordPart::Literal(&word[literal_start..]));
+    }
+    parts
+}
+
+/// Parse the expansion after a `$` and return it with the number of consumed bytes.
+fn parse_expansion(after: &str) -> Option<(WordPart<'_>, usize)> {
+    if let Some(inner) = after.strip_prefix('(') {
+        let end = find_closing_paren(inner);
+        let close = usize::from(end < inner.len());
+        Some((WordPart::Substitution(&inner[..end]), 1 + end + close))
+    } else if let Some(inner) = after.strip_prefix('{')
+        && let Some(end) = inner.find('}')
+    {
+        Some((WordPart::Variable(&inner[..end]), end + 2))
+    } else if after.starts_with('?') {
+        Some((WordPart::Variable("?"), 1))
+    } else {
+        let end = after
+            .find(|c: char| !is_variable_char(c))
+            .unwrap_or(after.len());
+        (end > 0).then(|| (WordPart::Variable(&after[..end]), end))
+    }
+}
+
+pub fn is_variable_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Return the index of the paren that closes an already opened paren
+/// or the length of the input if there is none.
+fn find_closing_paren(src: &str) -> usize {
+    let mut depth = 0usize;
+    for (index, c) in src.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return index,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    src.len()
+}
+
+/// Why an input line must be continued on the next line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Continuation {
+    /// The line ends in a backslash which escapes the line break.
+    Backslash,
+    /// The line contains an unclosed quote or bracket.
+    Unclosed,
+}
+
+/// Return whether the input is incomplete and must be continued on the next line.
+///
+/// Brackets only need to be closed outside of quotes, and closing brackets
+/// without a matching opening bracket are ignored.
+pub fn continuation(src: &str) -> Option<Continuation> {
+    let mut brackets = Vec::new();
+    let mut quote = None;
+    let mut chars = src.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                if chars.next().is_none() {
+                    return Some(Continuation::Backslash);
+                }
+            }
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[' | '{') => brackets.push(c),
+            (None, ')' | ']' | '}') => {
+                if brackets.last() == Some(&matching_bracket(c)) {
+                    brackets.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+    (quote.is_some() || !brackets.is_empty()).then_some(Continuation::Unclosed)
+}
+
+fn matching_bracket(closing: char) -> char {
+    match closing {
+        ')' => '(',
+        ']' => '[',
+        _ => '{',
+    }
+}
+
 #[derive(Debug)]
 pub struct Parser<'a> {
     scanner: Scanner<'a>,
@@ -116,7 +256,14 @@ impl<'a> Parser<'a> {
             && !self.scanner.is_finished()
             && !terminator(self.scanner.current())
         {
-            self.eat();
+            match self.scanner.current() {
+                '$' if self.scanner.peek() == '(' => self.eat_substitution(),
+                '\\' => self.eat_escaped(),
+                quote @ ('\'' | '"') => self.eat_quoted(quote),
+                _ => {
+                    self.eat();
+                }
+            }
         }
         // self.current_range.extend_to(self.scanner.current_index());
         StringExpression {
@@ -177,6 +324,56 @@ impl<'a> Parser<'a> {
         res
     }
 
+    /// Eat a command substitution `$(...)` including whitespace and nested parens.
+    fn eat_substitution(&mut self) {
+        self.eat(); // '$'
+        self.eat(); // '('
+        let mut depth = 0usize;
+        while !self.scanner.is_finished() {
+            match self.eat_current() {
+                '(' => depth += 1,
+                ')' if depth == 0 => return,
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Eat a backslash and the char it escapes.
+    fn eat_escaped(&mut self) {
+        self.eat(); // '\\'
+        if !self.scanner.is_finished() {
+            self.eat();
+        }
+    }
+
+    /// Eat a quoted string including the quotes.
+    ///
+    /// Backslashes escape chars in double quotes but not in single quotes.
+    /// An unclosed quote extends to the end of the input.
+    fn eat_quoted(&mut self, quote: char) {
+        self.eat(); // opening quote
+        while !self.scanner.is_finished() {
+            match self.scanner.current() {
+                c if c == quote => {
+                    self.eat();
+                    return;
+                }
+                '\\' if quote == '"' => self.eat_escaped(),
+                _ => {
+                    self.eat();
+                }
+            }
+        }
+    }
+
+    /// Eat the current char and return it.
+    fn eat_current(&mut self) -> char {
+        let current = self.scanner.current();
+        self.eat();
+        current
+    }
+
     fn eat_whitespace(&mut self) {
         while self.scanner.current().is_whitespace() {
             self.eat();
@@ -437,4 +634,180 @@ mod tests {
         });
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn parse_command_substitution_with_spaces() {
+        let line = "cd $(find --plain (a|b))/data x";
+        let mut parser = Parser::new(line);
+        let parsed = parser.parse();
+        let expected = Call(CallExpression {
+            function: StringExpression {
+                range: TextRange::from((0, 2)),
+            },
+            arguments: vec![
+                Argument::Plain(StringExpression {
+                    range: TextRange::from((3, 29)),
+                }),
+                Argument::Plain(StringExpression {
+                    range: TextRange::from((30, 31)),
+                }),
+            ],
+            range: TextRange::from((0, 31)),
+        });
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn parse_command_unclosed_substitution() {
+        let line = "cd $(ls -l";
+        let mut parser = Parser::new(line);
+        let parsed = parser.parse();
+        let expected = Call(CallExpression {
+            function: StringExpression {
+                range: TextRange::from((0, 2)),
+            },
+            arguments: vec![Argument::Plain(StringExpression {
+                range: TextRange::from((3, 10)),
+            })],
+            range: TextRange::from((0, 10)),
+        });
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn parse_command_quoted_args() {
+        let line = r#"ls 'a b' "c\" d" e\ f"#;
+        let mut parser = Parser::new(line);
+        let parsed = parser.parse();
+        let expected = Call(CallExpression {
+            function: StringExpression {
+                range: TextRange::from((0, 2)),
+            },
+            arguments: vec![
+                Argument::Plain(StringExpression {
+                    range: TextRange::from((3, 8)),
+                }),
+                Argument::Plain(StringExpression {
+                    range: TextRange::from((9, 16)),
+                }),
+                Argument::Plain(StringExpression {
+                    range: TextRange::from((17, 21)),
+                }),
+            ],
+            range: TextRange::from((0, 21)),
+        });
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn parse_command_quoted_long_arg() {
+        let line = "find --name='a=b c'";
+        let mut parser = Parser::new(line);
+        let parsed = parser.parse();
+        let Call(call) = parsed else {
+            panic!("expected a call");
+        };
+        assert_eq!(call.arguments.len(), 2);
+        assert_eq!(call.arguments[0].get_content(line), "--name");
+        assert_eq!(call.arguments[1].get_content(line), "'a=b c'");
+    }
+
+    #[test]
+    fn parse_command_unclosed_quote() {
+        let line = "ls 'a b";
+        let mut parser = Parser::new(line);
+        let parsed = parser.parse();
+        let expected = Call(CallExpression {
+            function: StringExpression {
+                range: TextRange::from((0, 2)),
+            },
+            arguments: vec![Argument::Plain(StringExpression {
+                range: TextRange::from((3, 7)),
+            })],
+            range: TextRange::from((0, 7)),
+        });
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn split_word_literal() {
+        assert_eq!(
+            split_word("/entry/data"),
+            vec![WordPart::Literal("/entry/data")]
+        );
+    }
+
+    #[test]
+    fn split_word_variables() {
+        assert_eq!(
+            split_word("$base/${name}_x$?"),
+            vec![
+                WordPart::Variable("base"),
+                WordPart::Literal("/"),
+                WordPart::Variable("name"),
+                WordPart::Literal("_x"),
+                WordPart::Variable("?"),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_word_lone_dollar() {
+        assert_eq!(split_word("^det$"), vec![WordPart::Literal("^det$")]);
+        assert_eq!(split_word("a$-b"), vec![WordPart::Literal("a$-b")]);
+    }
+
+    #[test]
+    fn split_word_substitution() {
+        assert_eq!(
+            split_word("$(find --plain (a|b))/data"),
+            vec![
+                WordPart::Substitution("find --plain (a|b)"),
+                WordPart::Literal("/data"),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_word_quoted() {
+        assert_eq!(
+            split_word("'$a'\\$b\"$c\""),
+            vec![
+                WordPart::Literal("'$a'\\$b\""),
+                WordPart::Variable("c"),
+                WordPart::Literal("\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_word_unclosed_substitution() {
+        assert_eq!(split_word("$(ls"), vec![WordPart::Substitution("ls")]);
+    }
+
+    #[test]
+    fn continuation_complete() {
+        assert_eq!(continuation(""), None);
+        assert_eq!(continuation("ls -l"), None);
+        assert_eq!(continuation("cd $(find --plain (a|b))"), None);
+        assert_eq!(continuation("ls 'a(' \"[b\" c\\["), None);
+        assert_eq!(continuation("ls a)"), None);
+    }
+
+    #[test]
+    fn continuation_backslash() {
+        assert_eq!(continuation("ls \\"), Some(Continuation::Backslash));
+        assert_eq!(continuation("ls \"a\\"), Some(Continuation::Backslash));
+        assert_eq!(continuation("ls \\\\"), None);
+    }
+
+    #[test]
+    fn continuation_unclosed() {
+        assert_eq!(continuation("ls 'a"), Some(Continuation::Unclosed));
+        assert_eq!(continuation("ls \"a\\\""), Some(Continuation::Unclosed));
+        assert_eq!(continuation("foreach $(find"), Some(Continuation::Unclosed));
+        assert_eq!(continuation("ls a[0"), Some(Continuation::Unclosed));
+        assert_eq!(continuation("ls ${a"), Some(Continuation::Unclosed));
+        assert_eq!(continuation("ls $(a]"), Some(Continuation::Unclosed));
+    }
 }
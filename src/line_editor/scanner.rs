@@ -29,6 +29,11 @@ impl<'a> Scanner<'a> {
         self.current_index
     }
 
+    /// Return the char after the current one without advancing.
+    pub fn peek(&self) -> char {
+        self.chars.clone().next().unwrap_or('\0')
+    }
+
     pub fn is_finished(&self) -> bool {
         self.current == '\0'
     }
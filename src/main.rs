@@ -1,12 +1,17 @@
+mod bookmarks;
 mod cli;
 mod cmd;
 mod commands;
+mod config;
 mod data;
 mod h5;
+mod history;
+mod interrupt;
 mod line_editor;
 mod output;
 mod prompt;
 mod shell;
+mod units;
 
 use cmd::CommandOutcome;
 use line_editor::Poll;
@@ -24,8 +29,15 @@ fn main() -> ExitCode {
 }
 
 fn open_file(args: cli::OpenArgs) -> ExitCode {
-    let mut shell = shell::Shell::new(args.color);
-    let h5file = match h5::H5File::open(args.path.clone()) {
+    let mut config = config::Config::load(args.config.as_deref()).unwrap_or_else(|err| {
+        output::Printer::new(args.color).print_shell_error(format!("{err:#}"));
+        config::Config::default()
+    });
+    if let Some(edit_mode) = args.edit_mode {
+        config.editor.edit_mode = edit_mode;
+    }
+    let mut shell = shell::Shell::new(args.color, config);
+    let h5file = match h5::H5File::open(args.path.clone(), args.open_options.clone()) {
         Ok(h5file) => h5file,
         Err(err) => {
             shell
@@ -35,37 +47,57 @@ fn open_file(args: cli::OpenArgs) -> ExitCode {
         }
     };
 
+    shell.load_history(&h5file);
+    shell.load_bookmarks(&h5file);
     let Ok(mut editor) = shell.start_editor(&h5file) else {
         shell.printer().print_shell_error("Failed to start editor");
         return ExitCode::FAILURE;
     };
+    // The editor installs its own handler which would replace ours.
+    if let Err(err) = interrupt::install() {
+        shell
+            .printer()
+            .print_shell_error(format!("Failed to install Ctrl-C handler: {err}"));
+    }
     let mut exit_code = ExitCode::SUCCESS;
     loop {
-        match editor.poll(&shell, &h5file) {
-            Poll::Cmd(input) => match shell.parse_and_execute_input(&input, &h5file) {
-                CommandOutcome::KeepRunning => {}
-                CommandOutcome::ChangeWorkingGroup(new_working_group) => {
-                    shell.set_working_group(new_working_group.clone());
-                    editor.set_working_group(new_working_group);
-                }
-                CommandOutcome::ExitFailure => {
-                    exit_code = ExitCode::FAILURE;
-                    break;
-                }
-                CommandOutcome::ExitSuccess => {
-                    exit_code = ExitCode::SUCCESS;
-                    break;
-                }
-            },
-            Poll::Skip => {}
+        let input = match editor.poll(&shell, &h5file) {
+            Poll::Cmd(input) => {
+                let Some(input) = shell.expand_history(&input) else {
+                    continue;
+                };
+                editor.add_history_entry(&input);
+                shell.add_history_entry(&input);
+                input
+            }
+            Poll::Binding(input) => input,
+            Poll::Skip => continue,
             Poll::Exit => break,
             Poll::Error(err) => {
                 println!("ERROR {err}");
+                continue;
             }
+        };
+        interrupt::clear();
+        match shell.parse_and_execute_input(&input, &h5file) {
+            CommandOutcome::ExitFailure => {
+                exit_code = ExitCode::FAILURE;
+                break;
+            }
+            CommandOutcome::ExitSuccess => {
+                exit_code = ExitCode::SUCCESS;
+                break;
+            }
+            // All other outcomes are handled by the shell.
+            _ => {}
         }
     }
 
-    editor.save_history().unwrap();
+    if let Err(err) = shell.save_history() {
+        shell
+            .printer()
+            .print_shell_error(format!("Failed to save history: {err:#}"));
+    }
     exit_code
 }
 
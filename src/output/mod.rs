@@ -1,6 +1,8 @@
 mod printer;
+mod progress;
 pub mod style;
 mod table;
 
 pub use printer::Printer;
+pub use progress::Progress;
 pub use style::Style;
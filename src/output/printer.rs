@@ -1,6 +1,7 @@
-use super::Style;
 use super::style::{ATTRIBUTE_CHARACTER, DATASET_CHARACTER, GROUP_CHARACTER};
+use super::{Progress, Style};
 use crate::cmd::CommandError;
+use crate::config::StyleConfig;
 use crate::h5::{H5Object, H5Path};
 use bumpalo::{
     Bump,
@@ -11,23 +12,74 @@ use hdf5::{
     Location, LocationType,
     types::{FloatSize, IntSize, Reference, TypeDescriptor},
 };
+use std::cell::RefCell;
 use std::fmt::{Display, Formatter};
-use std::io::{Write, stderr, stdout};
+use std::io::{IsTerminal, Stdout, Write, stderr, stdout};
 use term_grid::{Direction, Filling, Grid, GridOptions};
 
 pub struct Printer {
     style: Style,
+    capture: Option<RefCell<Vec<u8>>>,
+}
+
+/// Regular output of a printer.
+///
+/// Writes either to stdout or a capture buffer.
+pub enum Output<'p> {
+    Stdout(Stdout),
+    Capture(&'p RefCell<Vec<u8>>),
 }
 
 impl Printer {
     pub fn new(stylize: bool) -> Self {
         Self {
             style: Style::new(stylize),
+            capture: None,
+        }
+    }
+
+    pub fn with_config(stylize: bool, config: &StyleConfig) -> Self {
+        Self {
+            style: Style::with_config(stylize, config),
+            capture: None,
+        }
+    }
+
+    /// Create an unstyled printer that captures regular output instead of writing to stdout.
+    ///
+    /// Errors are still written to stderr.
+    pub fn capturing() -> Self {
+        Self {
+            style: Style::new(false),
+            capture: Some(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Return the captured output, empty if the printer does not capture.
+    pub fn into_captured(self) -> String {
+        self.capture
+            .map(|capture| String::from_utf8_lossy(&capture.into_inner()).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Return a writer for regular output.
+    pub fn stdout(&self) -> Output<'_> {
+        match &self.capture {
+            Some(capture) => Output::Capture(capture),
+            None => Output::Stdout(stdout()),
         }
     }
 
+    /// Return a writer for regular output that can show progress on stderr.
+    ///
+    /// Progress is only shown if the output goes to a terminal.
+    pub fn progress(&self, label: &'static str) -> Progress<Output<'_>> {
+        let enabled = self.capture.is_none() && stderr().is_terminal() && stdout().is_terminal();
+        Progress::new(self.stdout(), label, enabled)
+    }
+
     pub fn println<T: Display>(&self, line: T) {
-        println!("{line}");
+        let _ = writeln!(self.stdout(), "{line}");
     }
 
     pub fn print_grid<T: AsRef<str>>(&self, cells: Vec<T>) {
@@ -39,7 +91,7 @@ impl Printer {
                 width: self.terminal_size().0 as usize,
             },
         );
-        let _ = stdout().write_all(grid.to_string().as_bytes());
+        let _ = self.stdout().write_all(grid.to_string().as_bytes());
     }
 
     pub fn print_cmd_error(&self, error: &CommandError) {
@@ -56,6 +108,15 @@ impl Printer {
                 );
             }
             CommandError::NoMessage => {}
+            CommandError::Interrupted => {
+                let _ = queue!(
+                    stderr,
+                    &self.style().error,
+                    Print("Interrupted"),
+                    self.style().reset(),
+                    Print("\n"),
+                );
+            }
             CommandError::Critical(message) => {
                 let _ = queue!(
                     stderr,
@@ -248,6 +309,22 @@ impl Printer {
     }
 }
 
+impl Write for Output<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::Capture(capture) => capture.borrow_mut().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::Capture(_) => Ok(()),
+        }
+    }
+}
+
 const BYTE_UNITS_SHORT: [&str; 5] = ["B ", "Ki", "Mi", "Gi", "Ti"];
 const BYTE_UNITS_LONG: [&str; 5] = ["B  ", "KiB", "MiB", "GiB", "TiB"];
 
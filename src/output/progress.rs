@@ -0,0 +1,96 @@
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveToColumn,
+    style::Print,
+    terminal::{Clear, ClearType},
+};
+use std::io::{Write, stderr};
+use std::time::{Duration, Instant};
+
+/// Operations that finish quicker than this do not show progress.
+const DELAY: Duration = Duration::from_millis(500);
+/// Minimum time between redraws of the status line.
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Status line on stderr for long-running operations.
+///
+/// Wraps the regular output and removes the status line before
+/// anything is written to the output so that the two do not mix.
+pub struct Progress<W: Write> {
+    output: W,
+    label: &'static str,
+    enabled: bool,
+    count: usize,
+    start: Instant,
+    last_draw: Option<Instant>,
+    shown: bool,
+}
+
+impl<W: Write> Progress<W> {
+    pub fn new(output: W, label: &'static str, enabled: bool) -> Self {
+        Self {
+            output,
+            label,
+            enabled,
+            count: 0,
+            start: Instant::now(),
+            last_draw: None,
+            shown: false,
+        }
+    }
+
+    /// Count one processed item and redraw the status line if it is due.
+    pub fn tick(&mut self) {
+        self.count += 1;
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        let due = match self.last_draw {
+            Some(last) => now - last >= INTERVAL,
+            None => now - self.start >= DELAY,
+        };
+        if due {
+            self.last_draw = Some(now);
+            let _ = self.draw();
+        }
+    }
+
+    /// Remove the status line and return the wrapped output.
+    pub fn finish(mut self) -> W {
+        let _ = self.clear();
+        self.output
+    }
+
+    fn draw(&mut self) -> std::io::Result<()> {
+        self.output.flush()?;
+        self.shown = true;
+        stderr()
+            .queue(MoveToColumn(0))?
+            .queue(Clear(ClearType::CurrentLine))?
+            .queue(Print(format_args!("{} {} objects", self.label, self.count)))?
+            .flush()
+    }
+
+    fn clear(&mut self) -> std::io::Result<()> {
+        if self.shown {
+            self.shown = false;
+            stderr()
+                .queue(MoveToColumn(0))?
+                .queue(Clear(ClearType::CurrentLine))?
+                .flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Progress<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.clear()?;
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.output.flush()
+    }
+}
@@ -2,6 +2,8 @@ use crossterm::style::{Attribute, Attributes, Color, Colors, SetAttributes, SetC
 use lscolors::{Indicator, LsColors};
 use std::fmt::Write;
 
+use crate::config::{ItemConfig, StyleConfig};
+
 pub const DATASET_CHARACTER: Option<char> = None;
 pub const GROUP_CHARACTER: Option<char> = Some('/');
 pub const ATTRIBUTE_CHARACTER: Option<char> = Some('@');
@@ -29,6 +31,13 @@ pub struct Style {
 pub struct Prompt {
     pub file_name: Item,
     pub working_group: Item,
+    pub nx_class: Item,
+    pub mode: Item,
+    pub children: Item,
+    pub file_size: Item,
+    pub status: Item,
+    pub duration: Item,
+    pub swmr: Item,
     pub char: Item,
 }
 
@@ -39,6 +48,7 @@ pub struct Editor {
     pub keyword_argument: Item,
     pub command: Item,
     pub bad_command: Item,
+    pub hint: Item,
 }
 
 #[derive(Clone, Debug)]
@@ -130,6 +140,49 @@ impl Style {
                         },
                         attributes: Attributes::default(),
                     },
+                    nx_class: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkCyan),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
+                    mode: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkGrey),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
+                    children: Item::default(),
+                    file_size: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkGreen),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
+                    status: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkRed),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
+                    duration: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkGrey),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
+                    swmr: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkMagenta),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
                     char: Item {
                         colors: Colors {
                             foreground: Some(Color::DarkRed),
@@ -162,6 +215,13 @@ impl Style {
                         },
                         attributes: Attribute::Bold.into(),
                     },
+                    hint: Item {
+                        colors: Colors {
+                            foreground: Some(Color::DarkGrey),
+                            background: None,
+                        },
+                        attributes: Attributes::default(),
+                    },
                 },
                 enabled: true,
             }
@@ -170,6 +230,55 @@ impl Style {
         }
     }
 
+    /// Create a style from the defaults with items overridden by the config.
+    pub fn with_config(enabled: bool, config: &StyleConfig) -> Self {
+        let mut style = Self::new(enabled);
+        if enabled {
+            style.apply_config(config);
+        }
+        style
+    }
+
+    fn apply_config(&mut self, config: &StyleConfig) {
+        fn apply(item: &mut Item, config: &Option<ItemConfig>) {
+            if let Some(config) = config {
+                *item = Item::from_config(config);
+            }
+        }
+
+        apply(&mut self.dataset, &config.dataset);
+        apply(&mut self.group, &config.group);
+        apply(&mut self.attribute, &config.attribute);
+        apply(&mut self.size, &config.size);
+        apply(&mut self.shape, &config.shape);
+        apply(&mut self.dtype, &config.dtype);
+        apply(&mut self.error, &config.error);
+        apply(&mut self.critical_error, &config.critical_error);
+        apply(&mut self.placeholder, &config.placeholder);
+        apply(&mut self.emphasis, &config.emphasis);
+
+        apply(&mut self.prompt.file_name, &config.prompt.file_name);
+        apply(&mut self.prompt.working_group, &config.prompt.working_group);
+        apply(&mut self.prompt.nx_class, &config.prompt.nx_class);
+        apply(&mut self.prompt.mode, &config.prompt.mode);
+        apply(&mut self.prompt.children, &config.prompt.children);
+        apply(&mut self.prompt.file_size, &config.prompt.file_size);
+        apply(&mut self.prompt.status, &config.prompt.status);
+        apply(&mut self.prompt.duration, &config.prompt.duration);
+        apply(&mut self.prompt.swmr, &config.prompt.swmr);
+        apply(&mut self.prompt.char, &config.prompt.char);
+
+        apply(&mut self.editor.plain, &config.editor.plain);
+        apply(&mut self.editor.pos_argument, &config.editor.pos_argument);
+        apply(
+            &mut self.editor.keyword_argument,
+            &config.editor.keyword_argument,
+        );
+        apply(&mut self.editor.command, &config.editor.command);
+        apply(&mut self.editor.bad_command, &config.editor.bad_command);
+        apply(&mut self.editor.hint, &config.editor.hint);
+    }
+
     pub fn reset(&self) -> Reset {
         Reset {
             enabled: self.enabled,
@@ -191,6 +300,20 @@ impl Item {
         }
     }
 
+    fn from_config(config: &ItemConfig) -> Self {
+        let mut attributes = Attributes::default();
+        for attribute in &config.attributes {
+            attributes.set(attribute.0);
+        }
+        Self {
+            colors: Colors {
+                foreground: config.fg.map(|color| color.0),
+                background: config.bg.map(|color| color.0),
+            },
+            attributes,
+        }
+    }
+
     pub fn reset(&self) -> Reset {
         Reset {
             enabled: !self.is_default(),
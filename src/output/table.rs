@@ -1,5 +1,6 @@
 use crate::data::load_and_format_data;
 use crate::h5::H5Object;
+use crate::interrupt;
 use crate::output::Printer;
 use bumpalo::{
     Bump,
@@ -299,7 +300,8 @@ fn format_content<'alloc>(
     printer: &Printer,
     bump: &'alloc Bump,
 ) -> std::io::Result<BumpString<'alloc>> {
-    if container.ndim() > 1 {
+    // Stop loading data after an interrupt, the caller reports it.
+    if container.ndim() > 1 || interrupt::is_interrupted() {
         data_placeholder(printer, bump)
     } else {
         let max_elem = if slice { Some(8) } else { None };
@@ -1,132 +1,224 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use bumpalo::Bump;
 use crossterm::{ExecutableCommand, style::Print};
+use log::info;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::h5::H5File;
+use crate::config::{PromptConfig, PromptModule, PromptSegment};
+use crate::data::load_and_format_data;
+use crate::h5::{H5File, H5Object};
+use crate::output::style::Item;
 use crate::shell::Shell;
 
+/// Rendered in place of a module that failed to render.
+const FALLBACK: &str = "?";
+
 pub struct Prompt {
-    modules: Vec<Module>,
+    segments: Vec<Segment>,
+    continuation: String,
 }
 
 impl Prompt {
-    pub fn new(style: &crate::output::Style) -> Self {
+    pub fn new(style: &crate::output::Style, config: &PromptConfig) -> Self {
         Self {
-            modules: vec![
-                Module::FileName {
-                    style: Style {
-                        item: style.prompt.file_name.clone(),
-                        ..Default::default()
-                    },
-                },
-                Module::WorkingGroup {
-                    style: Style {
-                        item: style.prompt.working_group.clone(),
-                        ..Default::default()
-                    },
-                },
-                Module::Char {
-                    c: String::from("$"),
-                    style: Style {
-                        item: style.prompt.char.clone(),
-                        padding_left: 1,
-                        padding_right: 1,
-                    },
-                },
-            ],
+            segments: config
+                .format
+                .0
+                .iter()
+                .map(|segment| match segment {
+                    PromptSegment::Literal(text) => Segment::Literal(text.clone()),
+                    PromptSegment::Module(module) => {
+                        Segment::Module(Module::new(*module, style, config))
+                    }
+                })
+                .collect(),
+            continuation: styled(&style.prompt.char, &config.continuation)
+                .unwrap_or_else(|_| config.continuation.clone()),
         }
     }
 
-    pub fn render(&self, shell: &Shell, h5file: &H5File) -> String {
-        self.render_modules(shell, h5file)
-            .unwrap_or_else(|_| String::from("$"))
+    /// The prompt for lines that continue a command.
+    pub fn continuation(&self) -> &str {
+        &self.continuation
     }
 
-    fn render_modules(&self, shell: &Shell, h5file: &H5File) -> Result<String> {
-        let mut buffer: Vec<u8> = Vec::new();
-        for module in &self.modules {
-            module.render(&mut buffer, shell, h5file)?;
+    pub fn render(&self, shell: &Shell, h5file: &H5File) -> String {
+        let mut prompt = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => prompt.push_str(text),
+                Segment::Module(module) => match module.render(shell, h5file) {
+                    Ok(rendered) => prompt.push_str(&rendered),
+                    Err(err) => {
+                        info!("Failed to render prompt module: {err}");
+                        prompt.push_str(FALLBACK);
+                    }
+                },
+            }
         }
-        Ok(String::from_utf8(buffer)?)
+        prompt
     }
 }
 
-enum Module {
-    FileName { style: Style },
-    WorkingGroup { style: Style },
-    Char { c: String, style: Style },
+enum Segment {
+    Literal(String),
+    Module(Module),
 }
 
-#[derive(Default)]
-struct Style {
-    item: crate::output::style::Item,
-    padding_left: usize,
-    padding_right: usize,
+enum Module {
+    FileName { style: Item },
+    WorkingGroup { style: Item },
+    NxClass { style: Item },
+    Mode { style: Item },
+    Children { style: Item },
+    FileSize { style: Item },
+    Status { style: Item },
+    Duration { style: Item },
+    Swmr { style: Item },
+    Char { c: String, style: Item },
 }
 
 impl Module {
-    fn render<Out: ExecutableCommand>(
-        &self,
-        out: &mut Out,
-        shell: &Shell,
-        h5file: &H5File,
-    ) -> Result<()> {
+    fn new(module: PromptModule, style: &crate::output::Style, config: &PromptConfig) -> Self {
+        let style = &style.prompt;
+        match module {
+            PromptModule::FileName => Module::FileName {
+                style: style.file_name.clone(),
+            },
+            PromptModule::WorkingGroup => Module::WorkingGroup {
+                style: style.working_group.clone(),
+            },
+            PromptModule::NxClass => Module::NxClass {
+                style: style.nx_class.clone(),
+            },
+            PromptModule::Mode => Module::Mode {
+                style: style.mode.clone(),
+            },
+            PromptModule::Children => Module::Children {
+                style: style.children.clone(),
+            },
+            PromptModule::FileSize => Module::FileSize {
+                style: style.file_size.clone(),
+            },
+            PromptModule::Status => Module::Status {
+                style: style.status.clone(),
+            },
+            PromptModule::Duration => Module::Duration {
+                style: style.duration.clone(),
+            },
+            PromptModule::Swmr => Module::Swmr {
+                style: style.swmr.clone(),
+            },
+            PromptModule::Char => Module::Char {
+                c: config.char.clone(),
+                style: style.char.clone(),
+            },
+        }
+    }
+
+    fn render(&self, shell: &Shell, h5file: &H5File) -> Result<String> {
         match self {
-            Self::FileName { style } => render_filename(out, h5file, style),
-            Self::WorkingGroup { style } => render_working_group(out, shell, style),
-            Self::Char { c, style } => render_char(out, c, style),
+            Self::FileName { style } => styled(style, &file_name(h5file)),
+            Self::WorkingGroup { style } => styled(style, shell.get_working_group().as_raw()),
+            Self::NxClass { style } => match nx_class(shell, h5file)? {
+                Some(nx_class) => styled(style, &format!("({nx_class})")),
+                None => Ok(String::new()),
+            },
+            Self::Mode { style } => styled(style, if h5file.is_read_only() { "ro" } else { "rw" }),
+            Self::Children { style } => styled(style, &n_children(shell, h5file)?.to_string()),
+            Self::FileSize { style } => {
+                let bump = Bump::new();
+                let size = shell
+                    .printer()
+                    .format_human_size_in(h5file.size(), true, &bump);
+                styled(style, &size)
+            }
+            // Only failures are shown to keep the prompt short.
+            Self::Status { style } => match shell.last_status() {
+                Some(status) if status.code != 0 => styled(style, &status.code.to_string()),
+                _ => Ok(String::new()),
+            },
+            Self::Duration { style } => match shell.last_status() {
+                Some(status) => styled(style, &format_duration(status.duration)),
+                None => Ok(String::new()),
+            },
+            Self::Swmr { style } => {
+                if h5file.is_swmr() {
+                    styled(style, "swmr")
+                } else {
+                    Ok(String::new())
+                }
+            }
+            Self::Char { c, style } => styled(style, c),
         }
     }
 }
 
-fn render_filename<Out: ExecutableCommand>(
-    out: &mut Out,
-    h5file: &H5File,
-    style: &Style,
-) -> Result<()> {
+fn styled(style: &Item, text: &str) -> Result<String> {
+    let mut buffer: Vec<u8> = Vec::new();
+    buffer.execute(style)?;
+    buffer.execute(Print(text))?;
+    buffer.execute(style.reset())?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+fn file_name(h5file: &H5File) -> String {
     let path = PathBuf::from(h5file.filename());
-    let filename = path
-        .file_name()
-        .map_or_else(|| "", |s| s.to_str().unwrap_or(""));
-
-    style.start(out)?;
-    out.execute(Print(filename))?;
-    style.end(out)?;
-    Ok(())
+    path.file_name()
+        .map_or_else(|| "", |s| s.to_str().unwrap_or(""))
+        .to_string()
 }
 
-fn render_working_group<Out: ExecutableCommand>(
-    out: &mut Out,
-    shell: &Shell,
-    style: &Style,
-) -> Result<()> {
-    style.start(out)?;
-    out.execute(Print(shell.get_working_group()))?;
-    style.end(out)?;
-    Ok(())
+fn nx_class(shell: &Shell, h5file: &H5File) -> Result<Option<String>> {
+    let H5Object::Group(group) = h5file.load(shell.get_working_group())? else {
+        bail!("Working group is not a group");
+    };
+    if !group.attr_names()?.iter().any(|name| name == "NX_class") {
+        return Ok(None);
+    }
+    let attr = group.attr("NX_class")?;
+    let bump = Bump::new();
+    let value = load_and_format_data(attr.underlying(), None, None, shell.printer(), &bump)?;
+    Ok(Some(value.to_string()))
 }
 
-fn render_char<Out: ExecutableCommand>(out: &mut Out, c: &str, style: &Style) -> Result<()> {
-    style.start(out)?;
-    out.execute(Print(c))?;
-    style.end(out)?;
-    Ok(())
+fn n_children(shell: &Shell, h5file: &H5File) -> Result<u64> {
+    match h5file.load(shell.get_working_group())? {
+        H5Object::Group(group) => Ok(group.underlying().len()),
+        _ => bail!("Working group is not a group"),
+    }
 }
 
-impl Style {
-    fn start<Out: ExecutableCommand>(&self, out: &mut Out) -> Result<()> {
-        if self.padding_left > 0 {
-            out.execute(Print(" ".repeat(self.padding_left)))?;
-        }
-        out.execute(&self.item)?;
-        Ok(())
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{millis}ms")
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        let secs = duration.as_secs();
+        format!("{}m{}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_duration_millis() {
+        assert_eq!(format_duration(Duration::from_micros(12_345)), "12ms");
     }
 
-    fn end<Out: ExecutableCommand>(&self, out: &mut Out) -> Result<()> {
-        out.execute(self.item.reset())?;
-        if self.padding_right > 0 {
-            out.execute(Print(" ".repeat(self.padding_right)))?;
-        }
-        Ok(())
+    #[test]
+    fn format_duration_seconds() {
+        assert_eq!(format_duration(Duration::from_millis(2_345)), "2.3s");
+    }
+
+    #[test]
+    fn format_duration_minutes() {
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m5s");
     }
 }
@@ -1,23 +1,64 @@
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
+use crate::bookmarks::Bookmarks;
 use crate::cmd::{self, Command, CommandError, CommandOutcome};
-use crate::h5::{H5File, H5Path};
+use crate::config::Config;
+use crate::h5::{self, H5File, H5FileCache, H5Path, ObjectPath, glob};
+use crate::history::History;
+use crate::interrupt;
 use crate::line_editor::LineEditor;
-use crate::line_editor::parse::{Argument, Expression, Parser};
+use crate::line_editor::parse::{
+    Argument, Expression, Parser, WordPart, is_variable_char, split_word,
+};
 use crate::output::Printer;
 
 pub struct Shell {
     working_group: H5Path,
+    /// The working group before the last change, for `cd -`.
+    previous_group: Option<H5Path>,
+    /// Groups saved by `pushd`, the top of the stack is last.
+    group_stack: Vec<H5Path>,
     printer: Printer,
     commands: cmd::Commands,
+    config: Config,
+    last_status: Option<CommandStatus>,
+    variables: IndexMap<String, String>,
+    history: History,
+    bookmarks: Bookmarks,
+}
+
+/// Variables that are provided by the shell and cannot be set by users.
+const BUILTIN_VARIABLES: [&str; 3] = ["PWD", "FILE", "?"];
+
+/// Exit status and run time of a command.
+#[derive(Clone, Copy, Debug)]
+pub struct CommandStatus {
+    /// 0 on success, 1 on failure.
+    pub code: u8,
+    pub duration: Duration,
 }
 
 impl Shell {
-    pub fn new(stylize_output: bool) -> Self {
+    pub fn new(stylize_output: bool, config: Config) -> Self {
+        let mut commands = cmd::Commands::new();
+        for (name, flags) in &config.default_flags {
+            commands.set_default_flags(name, flags);
+        }
         Self {
             working_group: H5Path::root(),
-            printer: Printer::new(stylize_output),
-            commands: cmd::Commands::new(),
+            previous_group: None,
+            group_stack: Vec::new(),
+            printer: Printer::with_config(stylize_output, &config.style),
+            commands,
+            history: History::new(&config.history, String::new()),
+            bookmarks: Bookmarks::new(bookmarks_path(&config), String::new()),
+            config,
+            last_status: None,
+            variables: IndexMap::new(),
         }
     }
 
@@ -38,7 +79,111 @@ impl Shell {
     }
 
     pub fn set_working_group(&mut self, path: H5Path) {
-        self.working_group = path;
+        if path != self.working_group {
+            self.previous_group = Some(std::mem::replace(&mut self.working_group, path));
+        }
+    }
+
+    pub fn previous_group(&self) -> Option<&H5Path> {
+        self.previous_group.as_ref()
+    }
+
+    pub fn group_stack(&self) -> &[H5Path] {
+        &self.group_stack
+    }
+
+    /// Status of the most recently executed command, if any.
+    pub fn last_status(&self) -> Option<&CommandStatus> {
+        self.last_status.as_ref()
+    }
+
+    pub fn is_builtin_variable(name: &str) -> bool {
+        BUILTIN_VARIABLES.contains(&name)
+    }
+
+    /// Return the value of a builtin or user-defined variable.
+    pub fn get_variable(&self, name: &str, h5file: &H5File) -> Option<String> {
+        match name {
+            "PWD" => Some(self.working_group.to_string()),
+            "FILE" => Some(h5file.filename()),
+            "?" => Some(self.last_status.map_or(0, |status| status.code).to_string()),
+            _ => self.variables.get(name).cloned(),
+        }
+    }
+
+    pub fn set_variable(&mut self, name: String, value: String) {
+        self.variables.insert(name, value);
+    }
+
+    /// Return all variables with their values, builtins first.
+    pub fn variables(&self, h5file: &H5File) -> Vec<(String, String)> {
+        BUILTIN_VARIABLES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(self.variables.keys().cloned())
+            .filter_map(|name| {
+                let value = self.get_variable(&name, h5file)?;
+                Some((name, value))
+            })
+            .collect()
+    }
+
+    /// Return the names of all variables that can be referenced by name.
+    pub fn variable_names(&self) -> impl Iterator<Item = &str> {
+        BUILTIN_VARIABLES
+            .iter()
+            .copied()
+            .filter(|name| name.chars().all(is_variable_char))
+            .chain(self.variables.keys().map(String::as_str))
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Load the history and tag new entries with the given file.
+    pub fn load_history(&mut self, h5file: &H5File) {
+        self.history = History::load(&self.config.history, file_key(h5file));
+    }
+
+    pub fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    /// Load the bookmarks of the given file and the global bookmarks.
+    pub fn load_bookmarks(&mut self, h5file: &H5File) {
+        self.bookmarks = Bookmarks::load(bookmarks_path(&self.config), file_key(h5file));
+    }
+
+    pub fn save_history(&self) -> anyhow::Result<()> {
+        self.history.save()
+    }
+
+    pub fn add_history_entry(&mut self, input: &str) {
+        self.history.add(input);
+    }
+
+    /// Replace references to the history like `!!` in an input line.
+    ///
+    /// Prints the expanded line if it differs from the input.
+    /// Returns `None` and sets the exit status if a reference cannot be resolved.
+    pub fn expand_history(&mut self, input: &str) -> Option<String> {
+        match self.history.expand(input) {
+            Ok(expanded) => {
+                if expanded != input {
+                    self.printer().println(&expanded);
+                }
+                Some(expanded)
+            }
+            Err(err) => {
+                self.printer().print_shell_error(err);
+                self.last_status = Some(CommandStatus {
+                    code: 1,
+                    duration: Duration::ZERO,
+                });
+                None
+            }
+        }
     }
 
     pub fn resolve_path(&self, path: &H5Path) -> H5Path {
@@ -48,28 +193,299 @@ impl Shell {
     pub fn start_editor<'f>(&self, file: &'f H5File) -> rustyline::Result<LineEditor<'f>> {
         LineEditor::new(
             self.commands.keys().cloned().collect(),
+            self.commands.arg_parsers(),
             file,
             self.printer().style(),
+            &self.config,
+            &self.history,
         )
     }
 
     pub fn parse_and_execute_input(&mut self, input: &str, h5file: &H5File) -> CommandOutcome {
+        let start = Instant::now();
+        let (outcome, code) = self.execute_input(input, h5file);
+        let (outcome, code) = self.apply_outcome(outcome, code, h5file);
+        self.last_status = Some(CommandStatus {
+            code,
+            duration: start.elapsed(),
+        });
+        outcome
+    }
+
+    /// Apply changes to the shell state requested by a command.
+    ///
+    /// Returns `KeepRunning` or an exit outcome.
+    fn apply_outcome(
+        &mut self,
+        outcome: CommandOutcome,
+        code: u8,
+        h5file: &H5File,
+    ) -> (CommandOutcome, u8) {
+        match outcome {
+            CommandOutcome::ChangeWorkingGroup(path) => {
+                self.set_working_group(path);
+                (CommandOutcome::KeepRunning, code)
+            }
+            CommandOutcome::ChangeGroupStack {
+                working_group,
+                stack,
+            } => {
+                self.set_working_group(working_group);
+                self.group_stack = stack;
+                (CommandOutcome::KeepRunning, code)
+            }
+            CommandOutcome::SetVariable { name, value } => {
+                self.set_variable(name, value);
+                (CommandOutcome::KeepRunning, code)
+            }
+            CommandOutcome::AddBookmark { name, path, scope } => {
+                match self.bookmarks.add(name, &path, scope) {
+                    Ok(()) => (CommandOutcome::KeepRunning, code),
+                    Err(err) => {
+                        self.printer
+                            .print_shell_error(format!("Failed to save bookmarks: {err:#}"));
+                        (CommandOutcome::KeepRunning, 1)
+                    }
+                }
+            }
+            CommandOutcome::RemoveBookmark { name, scope } => {
+                match self.bookmarks.remove(&name, scope) {
+                    Ok(_) => (CommandOutcome::KeepRunning, code),
+                    Err(err) => {
+                        self.printer
+                            .print_shell_error(format!("Failed to save bookmarks: {err:#}"));
+                        (CommandOutcome::KeepRunning, 1)
+                    }
+                }
+            }
+            CommandOutcome::Foreach {
+                inputs,
+                stop_on_error,
+            } => self.execute_each(&inputs, stop_on_error, h5file),
+            outcome => (outcome, code),
+        }
+    }
+
+    fn execute_each(
+        &mut self,
+        inputs: &[String],
+        stop_on_error: bool,
+        h5file: &H5File,
+    ) -> (CommandOutcome, u8) {
+        let mut n_failed = 0;
+        for input in inputs {
+            match self.parse_and_execute_input(input, h5file) {
+                CommandOutcome::KeepRunning => {}
+                outcome => return (outcome, self.last_status.map_or(0, |status| status.code)),
+            }
+            if interrupt::is_interrupted() {
+                return (CommandOutcome::KeepRunning, 130);
+            }
+            if self.last_status.is_some_and(|status| status.code != 0) {
+                n_failed += 1;
+                if stop_on_error {
+                    self.printer()
+                        .print_shell_error(format!("foreach: stopped after failure of '{input}'"));
+                    return (CommandOutcome::KeepRunning, 1);
+                }
+            }
+        }
+        if n_failed > 0 {
+            self.printer().print_shell_error(format!(
+                "foreach: {n_failed} of {} commands failed",
+                inputs.len()
+            ));
+            (CommandOutcome::KeepRunning, 1)
+        } else {
+            (CommandOutcome::KeepRunning, 0)
+        }
+    }
+
+    fn execute_input(&mut self, input: &str, h5file: &H5File) -> (CommandOutcome, u8) {
         let (cmd, args) = parse_and_resolve_input(input, &self.commands);
+        let args = self.expand_args(args, h5file);
         let Some(cmd) = self.get_command(&cmd) else {
             self.printer()
                 .print_shell_error(format!("Unknown command: {cmd}"));
-            return CommandOutcome::KeepRunning;
+            return (CommandOutcome::KeepRunning, 1);
+        };
+        let args = match self.expand_paths(cmd.as_ref(), args, h5file) {
+            Ok(args) => args,
+            Err(err) => {
+                self.printer().print_shell_error(err);
+                return (CommandOutcome::KeepRunning, 1);
+            }
         };
         match self.parse_and_run_command(cmd, &args, h5file) {
-            Ok(outcome) => outcome,
+            Ok(outcome) => (outcome, 0),
             Err(err) => {
                 self.printer().print_cmd_error(&err);
                 match err {
-                    CommandError::Critical(_) => CommandOutcome::ExitFailure,
-                    _ => CommandOutcome::KeepRunning,
+                    CommandError::Critical(_) => (CommandOutcome::ExitFailure, 1),
+                    CommandError::Interrupted => (CommandOutcome::KeepRunning, 130),
+                    _ => (CommandOutcome::KeepRunning, 1),
+                }
+            }
+        }
+    }
+
+    /// Expand variable references and command substitutions in arguments.
+    ///
+    /// The output of substituted commands is split at whitespace
+    /// while variables always expand to a single word.
+    /// Quotes in expanded values are escaped so that they are not removed later.
+    fn expand_args(&mut self, args: Vec<String>, h5file: &H5File) -> Vec<String> {
+        let mut expanded = Vec::with_capacity(args.len());
+        for arg in args {
+            let mut current: Option<String> = None;
+            for part in split_word(&arg) {
+                match part {
+                    WordPart::Literal(text) => current.get_or_insert_default().push_str(text),
+                    WordPart::Variable(name) => {
+                        let value = self.get_variable(name, h5file).unwrap_or_default();
+                        if !value.is_empty() {
+                            current
+                                .get_or_insert_default()
+                                .push_str(&escape_quotes(&value));
+                        }
+                    }
+                    WordPart::Substitution(input) => {
+                        let output = self.capture_output(input, h5file);
+                        let mut fields = output.split_whitespace();
+                        if let Some(first) = fields.next() {
+                            current
+                                .get_or_insert_default()
+                                .push_str(&escape_quotes(first));
+                        }
+                        for field in fields {
+                            expanded.extend(current.take());
+                            current = Some(escape_quotes(field));
+                        }
+                    }
+                }
+            }
+            expanded.extend(current);
+        }
+        expanded
+    }
+
+    /// Expand glob patterns in path arguments and remove quotes from all arguments.
+    ///
+    /// Path arguments are those with `ValueHint::AnyPath`. To find them, the arguments
+    /// are parsed with the command's parser. If that fails, the arguments are only
+    /// unquoted so that the command can report the error.
+    /// Locations in `ObjectPath` arguments are escaped to protect their `@`s.
+    fn expand_paths(
+        &self,
+        cmd: &dyn Command,
+        args: Vec<String>,
+        h5file: &H5File,
+    ) -> Result<Vec<String>, String> {
+        let is_candidate =
+            |arg: &String| !arg.starts_with('-') && arg.contains(['*', '?', '[', '\'', '"', '\\']);
+        if !args.iter().any(is_candidate) {
+            return Ok(args.iter().map(|arg| glob::unquote(arg)).collect());
+        }
+
+        // Replace candidates with placeholders to identify them after parsing.
+        let placeholder_prefix = '\u{1}';
+        let parser = cmd.arg_parser().no_binary_name(true);
+        let substituted = args.iter().enumerate().map(|(index, arg)| {
+            if is_candidate(arg) {
+                format!("{placeholder_prefix}{index}")
+            } else {
+                arg.clone()
+            }
+        });
+        let Ok(matches) = parser.clone().try_get_matches_from(substituted) else {
+            return Ok(args.iter().map(|arg| glob::unquote(arg)).collect());
+        };
+        let mut path_args = HashMap::new();
+        for arg in parser
+            .get_arguments()
+            .filter(|arg| arg.get_value_hint() == clap::ValueHint::AnyPath)
+        {
+            let id = arg.get_id().as_str();
+            let kind = PathArg {
+                multiple: matches!(arg.get_action(), clap::ArgAction::Append),
+                object_path: matches.try_get_many::<ObjectPath>(id).is_ok(),
+            };
+            let values = matches.try_get_raw(arg.get_id().as_str()).ok().flatten();
+            for value in values.into_iter().flatten() {
+                if let Some(index) = value
+                    .to_str()
+                    .and_then(|value| value.strip_prefix(placeholder_prefix))
+                    .and_then(|index| index.parse::<usize>().ok())
+                {
+                    path_args.insert(index, kind);
                 }
             }
         }
+
+        let mut expanded = Vec::with_capacity(args.len());
+        for (index, arg) in args.into_iter().enumerate() {
+            let Some(kind) = path_args.get(&index) else {
+                expanded.push(glob::unquote(&arg));
+                continue;
+            };
+            let (location, attr) = if kind.object_path {
+                glob::split_attr(&arg)
+            } else {
+                (arg.as_str(), None)
+            };
+
+            let locations = if glob::is_pattern(location) {
+                let paths = glob::expand(
+                    location,
+                    &self.working_group,
+                    &mut h5file.cache(),
+                    H5FileCache::child_loader(h5file),
+                    H5FileCache::glob_identifier(),
+                )
+                .map_err(glob_error)?;
+                if paths.len() > 1 && !kind.multiple {
+                    return Err(format!("Pattern matches more than one path: {arg}"));
+                }
+                paths.into_iter().map(|path| path.to_string()).collect()
+            } else {
+                vec![glob::unquote(location)]
+            };
+
+            expanded.extend(locations.into_iter().map(|location| {
+                if kind.object_path {
+                    let location = ObjectPath::escape_location(&location);
+                    match attr {
+                        Some(attr) => format!("{location}@{}", glob::unquote(attr)),
+                        None => location,
+                    }
+                } else {
+                    location
+                }
+            }));
+        }
+        Ok(expanded)
+    }
+
+    /// Execute a command and return its regular output.
+    ///
+    /// Like in a subshell, changes to the shell state, e.g., of the working group,
+    /// are discarded.
+    fn capture_output(&mut self, input: &str, h5file: &H5File) -> String {
+        let printer = std::mem::replace(&mut self.printer, Printer::capturing());
+        let working_group = self.working_group.clone();
+        let previous_group = self.previous_group.clone();
+        let group_stack = self.group_stack.clone();
+        let variables = self.variables.clone();
+        let last_status = self.last_status;
+
+        let _ = self.parse_and_execute_input(input, h5file);
+
+        self.working_group = working_group;
+        self.previous_group = previous_group;
+        self.group_stack = group_stack;
+        self.variables = variables;
+        self.last_status = last_status;
+        std::mem::replace(&mut self.printer, printer).into_captured()
     }
 
     fn parse_and_run_command(
@@ -98,6 +514,34 @@ impl Shell {
     }
 }
 
+/// How the shell treats a path argument of a command.
+#[derive(Clone, Copy, Debug)]
+struct PathArg {
+    /// The argument accepts multiple values.
+    multiple: bool,
+    /// The argument is an `ObjectPath` instead of an `H5Path`.
+    object_path: bool,
+}
+
+/// Escape quotes and backslashes so that they are used literally.
+fn escape_quotes(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\'' | '"' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn glob_error(err: h5::H5Error) -> String {
+    match err {
+        h5::H5Error::Other(message) => message,
+        err => err.to_string(),
+    }
+}
+
 fn parse_and_resolve_input(src: &str, commands: &cmd::Commands) -> (String, Vec<String>) {
     let expression = Parser::new(src).parse();
 
@@ -109,7 +553,14 @@ fn parse_and_resolve_input(src: &str, commands: &cmd::Commands) -> (String, Vec<
                     &format!("{alias} {}", call.get_args_str(src)),
                     commands,
                 ),
-                None => (function.to_string(), collect_args(&call.arguments, src)),
+                None => {
+                    let mut args: Vec<String> = commands
+                        .get_default_flags(function)
+                        .map(|flags| flags.split_whitespace().map(String::from).collect())
+                        .unwrap_or_default();
+                    args.extend(collect_args(&call.arguments, src));
+                    (function.to_string(), args)
+                }
             }
         }
         Expression::String(string) => (string.get_content(src).to_string(), Vec::new()),
@@ -124,6 +575,17 @@ fn collect_args(arguments: &[Argument], src: &str) -> Vec<String> {
         .collect()
 }
 
+/// Identify a file in the history and bookmarks by its absolute path.
+fn file_key(h5file: &H5File) -> String {
+    let filename = h5file.filename();
+    std::fs::canonicalize(&filename).map_or(filename, |path| path.to_string_lossy().into_owned())
+}
+
+/// Bookmarks are stored next to the history file.
+fn bookmarks_path(config: &Config) -> PathBuf {
+    config.history.path().with_file_name("bookmarks.toml")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,6 +631,29 @@ mod tests {
         assert_eq!(args, vec!["-l", "path"]);
     }
 
+    #[test]
+    fn parse_and_resolve_input_default_flags() {
+        let input = "ls path";
+        let mut commands = cmd::Commands::new();
+        commands.set_default_flags("ls", "-l --type");
+
+        let (cmd, args) = parse_and_resolve_input(input, &commands);
+        assert_eq!(cmd, "ls");
+        assert_eq!(args, vec!["-l", "--type", "path"]);
+    }
+
+    #[test]
+    fn parse_and_resolve_input_alias_with_default_flags() {
+        let input = "l path";
+        let mut commands = cmd::Commands::new();
+        commands.add_alias("l", "ls -l");
+        commands.set_default_flags("ls", "--type");
+
+        let (cmd, args) = parse_and_resolve_input(input, &commands);
+        assert_eq!(cmd, "ls");
+        assert_eq!(args, vec!["--type", "-l", "path"]);
+    }
+
     #[test]
     fn parse_and_resolve_input_only_alias() {
         let input = "l";
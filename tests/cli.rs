@@ -32,9 +32,18 @@ fn exe_path() -> String {
 
 /** Launch h5sh as an interactive PTY session. */
 fn launch_h5sh() -> PtyReplSession {
+    launch_h5sh_with(&[])
+}
+
+fn launch_h5sh_with(args: &[&str]) -> PtyReplSession {
+    launch_h5sh_on(&data_path("test.h5"), args)
+}
+
+fn launch_h5sh_on(file: &str, args: &[&str]) -> PtyReplSession {
     let mut cmd = Command::new(exe_path());
-    cmd.arg(data_path("test.h5"))
+    cmd.arg(file)
         .arg("--color=never")
+        .args(args)
         .env("COLUMNS", "80");
 
     let mut h5sh = PtyReplSession::new(spawn_command(cmd, Some(200)).unwrap(), "$".to_owned())
@@ -114,6 +123,117 @@ fn cd_and_pwd() {
     assert_output_contains(send_command(&mut h5sh, "pwd"), "/base");
 }
 
+#[test]
+fn cd_previous_and_group_stack() {
+    let mut h5sh = launch_h5sh();
+    send_command_no_output(&mut h5sh, "cd base/sub-group");
+    send_command_no_output(&mut h5sh, "cd /base/g_empty");
+    // Can go back to the previous group
+    assert_output_contains(send_command(&mut h5sh, "cd -"), "/base/sub-group");
+    assert_output_contains(send_command(&mut h5sh, "cd -"), "/base/g_empty");
+    // Can save groups on the stack
+    assert_output_contains(
+        send_command(&mut h5sh, "pushd /base"),
+        "/base /base/g_empty",
+    );
+    assert_output_contains(send_command(&mut h5sh, "pushd"), "/base/g_empty /base");
+    assert_output_contains(send_command(&mut h5sh, "dirs"), "/base/g_empty /base");
+    // Can return to saved groups
+    assert_output_contains(send_command(&mut h5sh, "popd"), "/base");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base");
+}
+
+#[test]
+fn variables_and_substitution() {
+    let mut h5sh = launch_h5sh();
+    send_command_no_output(&mut h5sh, "set group=base/sub-group");
+    send_command_no_output(&mut h5sh, "cd $group");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base/sub-group");
+    assert_output_contains(send_command(&mut h5sh, "ls $PWD"), "nested_ds");
+
+    send_command_no_output(&mut h5sh, "cd /");
+    send_command_no_output(&mut h5sh, "cd $(fd g_empty)");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base/g_empty");
+}
+
+#[test]
+fn substitute_find_output() {
+    let mut h5sh = launch_h5sh();
+    send_command_no_output(&mut h5sh, "cd $(find --plain ^g_empty$)");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base/g_empty");
+
+    send_command_no_output(&mut h5sh, "cd /");
+    send_command_no_output(&mut h5sh, "set grp=$(find --plain ^sub-group$)");
+    send_command_no_output(&mut h5sh, "cd $grp");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base/sub-group");
+}
+
+#[test]
+fn foreach() {
+    let mut h5sh = launch_h5sh();
+    assert_output_contains(
+        send_command(&mut h5sh, "foreach base/g_empty base/sub-group -- ls {}"),
+        "nested_ds",
+    );
+    send_command_no_output(&mut h5sh, "foreach /base /base/sub-group -- cd");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base/sub-group");
+
+    send_command_no_output(&mut h5sh, "cd /");
+    assert_output_contains(
+        send_command(
+            &mut h5sh,
+            "foreach $(find --plain ^sub-group$) -- a {}@class",
+        ),
+        "TestGroup",
+    );
+}
+
+#[test]
+fn glob_patterns() {
+    let mut h5sh = launch_h5sh();
+    send_command_no_output(&mut h5sh, "cd base/sub-g*");
+    assert_output_contains(send_command(&mut h5sh, "pwd"), "/base/sub-group");
+
+    send_command_no_output(&mut h5sh, "cd /");
+    send_command_no_output(&mut h5sh, "cat base/label-utf?");
+    let output = read_all_lines(&mut h5sh);
+    assert_output_lines(output, vec!["This is a UTF-8 dataset"]);
+
+    send_command_no_output(&mut h5sh, "a base/*/nested_ds@testo base/*-utf8@testo1");
+    let output = read_all_lines(&mut h5sh);
+    assert_output_lines(
+        output,
+        vec![
+            "base/sub-group/nested_ds@testo:",
+            "() 16B  utf-8 testo@ nested ds",
+            "",
+            "base/label-utf8@testo1:",
+            "() 16B  utf-8 testo1@ test attribute 1",
+        ],
+    );
+
+    assert_output_contains(
+        send_command(&mut h5sh, "ls 'base/*'"),
+        "Not found: '/base/*'",
+    );
+    assert_output_contains(
+        send_command(&mut h5sh, "ls base/nothing*"),
+        "No matches for pattern: base/nothing*",
+    );
+}
+
+#[test]
+fn quoting() {
+    let mut h5sh = launch_h5sh();
+    assert_output_contains(
+        send_command(&mut h5sh, "cat \"base/label-\"utf8"),
+        "This is a UTF-8 dataset",
+    );
+    send_command_no_output(&mut h5sh, "set x='a  b'\\ c");
+    send_command_no_output(&mut h5sh, "set");
+    assert!(read_all_lines(&mut h5sh).contains(&"x=a  b c".to_string()));
+}
+
 #[test]
 fn ls_from_root() {
     let mut h5sh = launch_h5sh();
@@ -172,6 +292,19 @@ fn attr() {
     assert_output_lines(output, expected_lines);
 }
 
+#[test]
+fn attr_names_after_path() {
+    let mut h5sh = launch_h5sh();
+    send_command_no_output(&mut h5sh, "a base/sub-group class ascii");
+    let output = read_all_lines(&mut h5sh);
+
+    let expected_lines = vec![
+        "() 16B  utf-8 class@ TestGroup",
+        "() 16B  ascii ascii@ English only",
+    ];
+    assert_output_lines(output, expected_lines);
+}
+
 #[test]
 fn cat() {
     let mut h5sh = launch_h5sh();
@@ -223,6 +356,40 @@ fn fd_attr() {
     assert_output_lines(output, expected_lines);
 }
 
+#[test]
+fn fd_value() {
+    let mut h5sh = launch_h5sh();
+
+    send_command_no_output(&mut h5sh, "fd --contains NaN");
+    let output = read_all_lines(&mut h5sh);
+    let expected_lines = vec!["base/arrays/some_nan_inf  [0, 1]"];
+    assert_output_lines(output, expected_lines);
+
+    send_command_no_output(&mut h5sh, "fd @array --value 'x > 4'");
+    let output = read_all_lines(&mut h5sh);
+    let expected_lines = vec!["base/sub-group/", "  array  [2]"];
+    assert_output_lines(output, expected_lines);
+}
+
+#[test]
+fn fd_output_modes() {
+    let mut h5sh = launch_h5sh();
+
+    send_command_no_output(&mut h5sh, "fd --plain --type group empty");
+    let output = read_all_lines(&mut h5sh);
+    let expected_lines = vec!["base/g_empty"];
+    assert_output_lines(output, expected_lines);
+
+    send_command_no_output(&mut h5sh, "fd --count --dtype bool");
+    let output = read_all_lines(&mut h5sh);
+    let expected_lines = vec!["3"];
+    assert_output_lines(output, expected_lines);
+
+    send_command_no_output(&mut h5sh, "fd --exec 'inspect {}' ^all_true$");
+    let output = read_all_lines(&mut h5sh);
+    assert_output_contains(output.join("\n"), "All true: true");
+}
+
 #[test]
 fn inspect_dataset_int() {
     let mut h5sh = launch_h5sh();
@@ -471,3 +638,124 @@ fn self_cmd_help() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn info_default_driver() {
+    let mut h5sh = launch_h5sh();
+
+    send_command_no_output(&mut h5sh, "info");
+    let output = read_all_lines(&mut h5sh).join("\n");
+    assert_output_contains(&output, "test.h5");
+    assert_output_contains(&output, "Driver: sec2  SWMR: false");
+    assert_output_contains(&output, "Superblock: version 0");
+    assert_output_contains(
+        output,
+        "Closed cleanly: unknown, only recorded by superblock version 2 and later",
+    );
+}
+
+#[test]
+fn split_options_require_split_driver() -> Result<(), Box<dyn std::error::Error>> {
+    for option in ["--meta-ext", "--raw-ext"] {
+        let output = Command::new(exe_path())
+            .arg(data_path("test.h5"))
+            .args([option, ".h5"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?
+            .wait_with_output()?;
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("'--meta-ext' and '--raw-ext' require '--driver split'"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn open_with_core_driver() {
+    let mut h5sh = launch_h5sh_with(&["--driver", "core", "--libver", "v1.8"]);
+
+    send_command_no_output(&mut h5sh, "info");
+    let output = read_all_lines(&mut h5sh).join("\n");
+    assert_output_contains(&output, "Driver: core");
+    assert_output_contains(output, "v1.8");
+
+    send_command_no_output(&mut h5sh, "ls base/arrays/ints");
+    let output = read_all_lines(&mut h5sh);
+    assert_output_contains(output.join("\n"), "ints");
+}
+
+#[test]
+fn glob_in_cyclic_file() {
+    let path = std::env::temp_dir().join(format!("h5sh-cli-cyclic-{}.h5", std::process::id()));
+    let file = hdf5::File::create(&path).unwrap();
+    let a = file.create_group("a").unwrap();
+    a.new_dataset_builder()
+        .with_data(&[1, 2])
+        .create("data")
+        .unwrap();
+    a.link_soft("/", "up").unwrap();
+    let c = file.create_group("c").unwrap();
+    c.new_dataset_builder().with_data(&[3]).create("d").unwrap();
+    c.link_hard("/c", "loop").unwrap();
+    drop((a, c, file));
+
+    let mut h5sh = launch_h5sh_on(path.to_str().unwrap(), &[]);
+    assert_output_contains(send_command(&mut h5sh, "cat **/data"), "[1, 2]");
+    assert_output_contains(send_command(&mut h5sh, "cat **/d"), "[3]");
+
+    let _ = std::fs::remove_file(path);
+}
+
+/** Append values to a 1d dataset and make them visible to SWMR readers. */
+fn append_rows(dataset: &hdf5::Dataset, values: &[i64]) {
+    let start = dataset.shape()[0];
+    dataset.resize(start + values.len()).unwrap();
+    dataset.write_slice(values, start..).unwrap();
+    dataset.file().unwrap().flush().unwrap();
+}
+
+#[test]
+fn swmr_sees_new_rows() {
+    let path = std::env::temp_dir().join(format!("h5sh-cli-swmr-{}.h5", std::process::id()));
+    let file = hdf5::File::with_options()
+        .with_fapl(|fapl| fapl.libver_latest())
+        .create(&path)
+        .unwrap();
+    let dataset = file
+        .new_dataset::<i64>()
+        .shape(0..)
+        .chunk(4)
+        .create("data")
+        .unwrap();
+    append_rows(&dataset, &[1, 2, 3]);
+    let status = hdf5::sync::sync(|| unsafe { hdf5_sys::h5f::H5Fstart_swmr_write(file.id()) });
+    assert!(status >= 0);
+
+    let mut h5sh = launch_h5sh_on(path.to_str().unwrap(), &["--swmr"]);
+    assert_output_contains(send_command(&mut h5sh, "cat data"), "[1, 2, 3]");
+
+    // The hint shows the current shape.
+    append_rows(&dataset, &[4, 5]);
+    h5sh.send_line("cat data").unwrap();
+    h5sh.exp_string("(5) i64").unwrap();
+    h5sh.exp_string("[1, 2, 3, 4, 5]").unwrap();
+    h5sh.wait_for_prompt().unwrap();
+
+    append_rows(&dataset, &[6]);
+    send_command_no_output(&mut h5sh, "refresh");
+    assert_output_contains(send_command(&mut h5sh, "cat data"), "[1, 2, 3, 4, 5, 6]");
+
+    h5sh.send_line("watch -n 2 -s 0.1 data").unwrap();
+    h5sh.exp_string("5\r\n6\r\n").unwrap();
+    append_rows(&dataset, &[7, 8]);
+    h5sh.exp_string("7\r\n8\r\n").unwrap();
+    h5sh.send_control('c').unwrap();
+    h5sh.wait_for_prompt().unwrap();
+
+    drop(dataset);
+    drop(file);
+    let _ = std::fs::remove_file(path);
+}